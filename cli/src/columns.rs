@@ -0,0 +1,391 @@
+#![warn(clippy::all)]
+
+use derive_more::Display;
+use humantime::format_duration;
+use reqwest::Url;
+use std::borrow::Cow;
+use std::error::Error;
+use std::str::FromStr;
+use tvrank::imdb::ImdbTitle;
+use tvrank::utils::result::Res;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+#[derive(Debug, Display)]
+#[display(fmt = "{}")]
+pub enum ColumnsErr {
+  #[display(
+    fmt = "Unknown column `{}`, expected one of title, original, year, rating, votes, runtime, genres, type, id or link",
+    _0
+  )]
+  UnknownColumn(String),
+  #[display(fmt = "Invalid width `{}` for column `{}`", _1, _0)]
+  InvalidWidth(String, String),
+  #[display(fmt = "No columns given")]
+  Empty,
+}
+
+impl Error for ColumnsErr {}
+
+/// A column of the table output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+  Title,
+  Original,
+  Year,
+  Rating,
+  Votes,
+  Runtime,
+  Genres,
+  Type,
+  Id,
+  Link,
+  /// The query that matched a title, only displayed by the batch subcommand.
+  Query,
+}
+
+impl Column {
+  /// The columns that can be selected, in their default order.
+  const ALL: [Column; 10] = [
+    Column::Title,
+    Column::Original,
+    Column::Year,
+    Column::Rating,
+    Column::Votes,
+    Column::Runtime,
+    Column::Genres,
+    Column::Type,
+    Column::Id,
+    Column::Link,
+  ];
+
+  /// Columns are never shrunk below this width, or that of their header, to fit the terminal.
+  const MIN_WIDTH: usize = 10;
+
+  /// The name the column is selected by.
+  fn name(self) -> &'static str {
+    match self {
+      Column::Title => "title",
+      Column::Original => "original",
+      Column::Year => "year",
+      Column::Rating => "rating",
+      Column::Votes => "votes",
+      Column::Runtime => "runtime",
+      Column::Genres => "genres",
+      Column::Type => "type",
+      Column::Id => "id",
+      Column::Link => "link",
+      Column::Query => "query",
+    }
+  }
+
+  pub fn header(self) -> &'static str {
+    match self {
+      Column::Title => "Primary Title",
+      Column::Original => "Original Title",
+      Column::Year => "Year",
+      Column::Rating => "Rating",
+      Column::Votes => "Votes",
+      Column::Runtime => "Runtime",
+      Column::Genres => "Genres",
+      Column::Type => "Type",
+      Column::Id => "IMDB ID",
+      Column::Link => "IMDB Link",
+      Column::Query => "Query",
+    }
+  }
+
+  /// The width the column is limited to unless another one is given.
+  fn default_width(self) -> Option<usize> {
+    match self {
+      Column::Title => Some(50),
+      Column::Original | Column::Query => Some(30),
+      _ => None,
+    }
+  }
+
+  /// Whether the column may be shrunk to fit the terminal. Numbers, IDs and links are not
+  /// shrunk as they would be useless once truncated.
+  fn is_flexible(self) -> bool {
+    matches!(self, Column::Title | Column::Original | Column::Genres | Column::Query)
+  }
+
+  /// The width the column may be shrunk to in order to fit the terminal.
+  fn min_width(self) -> usize {
+    Self::MIN_WIDTH.max(self.header().width())
+  }
+
+  /// The text of the column for a title.
+  ///
+  /// # Arguments
+  ///
+  /// * `title` - The title to display.
+  /// * `imdb_url` - The URL IMDB links are relative to.
+  pub fn text(self, title: &ImdbTitle, imdb_url: &Url) -> Res<String> {
+    let text = match self {
+      Column::Title => title.primary_title().to_owned(),
+      Column::Original => title.original_title().unwrap_or_default().to_owned(),
+      Column::Year => title.start_year().map(|year| year.to_string()).unwrap_or_default(),
+      Column::Rating => title
+        .rating()
+        .map(|rating| format!("{}/100", rating.rating()))
+        .unwrap_or_default(),
+      Column::Votes => title.rating().map(|rating| rating.votes().to_string()).unwrap_or_default(),
+      Column::Runtime => title
+        .runtime()
+        .map(|runtime| format_duration(runtime).to_string())
+        .unwrap_or_default(),
+      Column::Genres => title.genres().to_string(),
+      Column::Type => title.title_type().to_string(),
+      Column::Id => title.title_id().to_string(),
+      Column::Link => imdb_url.join(&title.title_id().to_string())?.to_string(),
+      Column::Query => String::new(),
+    };
+
+    Ok(text)
+  }
+}
+
+impl FromStr for Column {
+  type Err = ColumnsErr;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let name = s.trim().to_lowercase();
+
+    match name.as_str() {
+      "primary-title" | "primary_title" => Ok(Column::Title),
+      "original-title" | "original_title" => Ok(Column::Original),
+      _ => Self::ALL
+        .into_iter()
+        .find(|column| column.name() == name)
+        .ok_or_else(|| ColumnsErr::UnknownColumn(s.trim().to_owned())),
+    }
+  }
+}
+
+/// A column along with the width it is limited to, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnSpec {
+  pub column: Column,
+  pub width: Option<usize>,
+}
+
+impl From<Column> for ColumnSpec {
+  fn from(column: Column) -> Self {
+    Self { column, width: column.default_width() }
+  }
+}
+
+/// The columns of the table output, in the order they are displayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns(Vec<ColumnSpec>);
+
+impl Default for Columns {
+  fn default() -> Self {
+    Self(Column::ALL.into_iter().map(ColumnSpec::from).collect())
+  }
+}
+
+impl FromStr for Columns {
+  type Err = ColumnsErr;
+
+  /// Parse a comma-separated list of NAME[:WIDTH] (e.g. "title:40,year,rating,link").
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut columns = Vec::new();
+
+    for spec in s.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+      let (name, width) = match spec.split_once(':') {
+        Some((name, width)) => (name, Some(width.trim())),
+        None => (spec, None),
+      };
+
+      let column = name.parse::<Column>()?;
+      let width = match width {
+        Some(width) => match width.parse::<usize>() {
+          Ok(width) if width > 0 => Some(width),
+          _ => return Err(ColumnsErr::InvalidWidth(name.trim().to_owned(), width.to_owned())),
+        },
+        None => column.default_width(),
+      };
+
+      columns.push(ColumnSpec { column, width });
+    }
+
+    if columns.is_empty() {
+      return Err(ColumnsErr::Empty);
+    }
+
+    Ok(Self(columns))
+  }
+}
+
+impl Columns {
+  /// The columns with the query that matched each title in front of them.
+  #[must_use]
+  pub fn with_query(&self) -> Self {
+    let mut columns = vec![ColumnSpec::from(Column::Query)];
+    columns.extend(self.0.iter().copied());
+    Self(columns)
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &ColumnSpec> {
+    self.0.iter()
+  }
+
+  /// Compute the width of each column so that the table fits in `max_width`, if given.
+  ///
+  /// Columns are as wide as their widest cell, up to their width limit. When the table is
+  /// still too wide, the widest flexible column is shrunk one character at a time until it
+  /// fits or no column can be shrunk any further.
+  ///
+  /// # Arguments
+  ///
+  /// * `rows` - The text of each row, one cell per column.
+  /// * `max_width` - The width of the terminal, if known.
+  pub fn layout(&self, rows: &[Vec<String>], max_width: Option<usize>) -> Vec<usize> {
+    let mut widths: Vec<usize> = self
+      .0
+      .iter()
+      .enumerate()
+      .map(|(i, spec)| {
+        let width = rows
+          .iter()
+          .map(|row| row[i].width())
+          .chain([spec.column.header().width()])
+          .max()
+          .unwrap_or(0);
+        spec.width.map_or(width, |limit| width.min(limit))
+      })
+      .collect();
+
+    let max_width = match max_width {
+      Some(max_width) => max_width,
+      None => return widths,
+    };
+
+    // Each column is padded by a space on either side and separated by a border.
+    let overhead = 3 * widths.len() + 1;
+
+    while overhead + widths.iter().sum::<usize>() > max_width {
+      let widest = widths
+        .iter()
+        .enumerate()
+        .filter(|&(i, &width)| self.0[i].column.is_flexible() && width > self.0[i].column.min_width())
+        .max_by_key(|&(_, &width)| width)
+        .map(|(i, _)| i);
+
+      match widest {
+        Some(i) => widths[i] -= 1,
+        None => break,
+      }
+    }
+
+    widths
+  }
+}
+
+/// Truncate text to the given display width, ending it with an ellipsis if it was truncated.
+///
+/// # Arguments
+///
+/// * `text` - The text to truncate.
+/// * `width` - The display width to truncate to.
+pub fn truncate(text: &str, width: usize) -> Cow<str> {
+  if text.width() <= width {
+    return Cow::Borrowed(text);
+  }
+
+  let mut result = String::new();
+  let mut result_width = 0;
+
+  for c in text.chars() {
+    let char_width = c.width().unwrap_or(0);
+
+    // Leave room for the ellipsis.
+    if result_width + char_width + 1 > width {
+      break;
+    }
+
+    result_width += char_width;
+    result.push(c);
+  }
+
+  result.push('…');
+  Cow::Owned(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::columns::{truncate, Column, ColumnSpec, Columns, ColumnsErr};
+
+  #[test]
+  fn test_from_str() {
+    let columns: Columns = "title:40, Year,RATING,link".parse().unwrap();
+    assert_eq!(
+      columns.iter().copied().collect::<Vec<_>>(),
+      [
+        ColumnSpec { column: Column::Title, width: Some(40) },
+        ColumnSpec { column: Column::Year, width: None },
+        ColumnSpec { column: Column::Rating, width: None },
+        ColumnSpec { column: Column::Link, width: None },
+      ]
+    );
+
+    let columns: Columns = "primary-title,original_title,genres:20".parse().unwrap();
+    assert_eq!(
+      columns.iter().copied().collect::<Vec<_>>(),
+      [
+        ColumnSpec { column: Column::Title, width: Some(50) },
+        ColumnSpec { column: Column::Original, width: Some(30) },
+        ColumnSpec { column: Column::Genres, width: Some(20) },
+      ]
+    );
+
+    assert!(
+      matches!("title,plot".parse::<Columns>(), Err(ColumnsErr::UnknownColumn(name)) if name == "plot")
+    );
+    assert!(matches!("query".parse::<Columns>(), Err(ColumnsErr::UnknownColumn(_))));
+    assert!(matches!("title:0".parse::<Columns>(), Err(ColumnsErr::InvalidWidth(..))));
+    assert!(matches!("title:wide".parse::<Columns>(), Err(ColumnsErr::InvalidWidth(..))));
+    assert!(matches!("title:-1".parse::<Columns>(), Err(ColumnsErr::InvalidWidth(..))));
+    assert!(matches!("".parse::<Columns>(), Err(ColumnsErr::Empty)));
+    assert!(matches!(" , ,".parse::<Columns>(), Err(ColumnsErr::Empty)));
+  }
+
+  #[test]
+  fn test_layout() {
+    let columns: Columns = "title,year,genres".parse().unwrap();
+    let rows = vec![
+      vec![
+        "The Lord of the Rings: The Fellowship of the Ring".to_owned(),
+        "2001".to_owned(),
+        "Action, Adventure, Drama".to_owned(),
+      ],
+      vec!["Alien".to_owned(), "1979".to_owned(), "Horror, Sci-Fi".to_owned()],
+    ];
+
+    // Columns are as wide as their widest cell or header, up to their width limit.
+    assert_eq!(columns.layout(&rows, None), [49, 4, 24]);
+    assert_eq!("title:20,year,genres".parse::<Columns>().unwrap().layout(&rows, None), [20, 4, 24]);
+    assert_eq!(columns.layout(&rows, Some(200)), [49, 4, 24]);
+
+    // The widest flexible column is shrunk first, and the year is never shrunk.
+    assert_eq!(columns.layout(&rows, Some(70)), [32, 4, 24]);
+    assert_eq!(columns.layout(&rows, Some(50)), [18, 4, 18]);
+
+    // Columns are not shrunk below their minimum width, even if the table does not fit.
+    assert_eq!(columns.layout(&rows, Some(10)), [13, 4, 10]);
+  }
+
+  #[test]
+  fn test_truncate() {
+    assert_eq!(truncate("Alien", 5), "Alien");
+    assert_eq!(truncate("Alien", 10), "Alien");
+    assert_eq!(truncate("Hello World", 5), "Hell…");
+    assert_eq!(truncate("Hello World", 1), "…");
+
+    // Wide characters take two columns each.
+    assert_eq!(truncate("日本語のタイトル", 16), "日本語のタイトル");
+    assert_eq!(truncate("日本語のタイトル", 7), "日本語…");
+    assert_eq!(truncate("日本語のタイトル", 8), "日本語…");
+  }
+}
@@ -0,0 +1,94 @@
+#![warn(clippy::all)]
+
+use crate::columns::Columns;
+use crate::print::{OutputFormat, RatingThresholds};
+use directories::ProjectDirs;
+use log::debug;
+use reqwest::Url;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tvrank::imdb::ImdbSortOrder;
+use tvrank::utils::result::Res;
+
+/// Deserialize an optional value from a string, as it is parsed on the command-line.
+fn from_str<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+  D: Deserializer<'de>,
+  T: FromStr,
+  T::Err: Display,
+{
+  String::deserialize(deserializer)?.parse().map(Some).map_err(de::Error::custom)
+}
+
+/// Settings of the table output.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableConfig {
+  /// The columns to display, as with `--columns`.
+  #[serde(deserialize_with = "from_str")]
+  pub columns: Option<Columns>,
+}
+
+/// Settings read from `config.toml` in the configuration directory, which command-line
+/// options take precedence over.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+  /// The output format, as with `--output`.
+  pub output: Option<OutputFormat>,
+  /// The sort order, as with `--sort`.
+  #[serde(deserialize_with = "from_str")]
+  pub sort: Option<ImdbSortOrder>,
+  /// The number of results to display, as with `--top`.
+  pub top: Option<usize>,
+  /// Whether to display colors, regardless of the NO_COLOR environment variable.
+  pub color: Option<bool>,
+  /// The directory the IMDB databases are stored in.
+  pub cache_dir: Option<PathBuf>,
+  /// How often the IMDB databases are rebuilt, e.g. "2 weeks".
+  #[serde(deserialize_with = "from_str")]
+  pub refresh_interval: Option<humantime::Duration>,
+  /// The URL the IMDB datasets are downloaded from, e.g. a mirror.
+  #[serde(deserialize_with = "from_str")]
+  pub dataset_url: Option<Url>,
+  /// The ratings at or above which titles are displayed in green or yellow.
+  pub ratings: RatingThresholds,
+  pub table: TableConfig,
+}
+
+impl Config {
+  /// Load the configuration file, or the default configuration if there is none.
+  ///
+  /// # Arguments
+  ///
+  /// * `project` - The directories of the application.
+  pub fn load(project: &ProjectDirs) -> Res<Self> {
+    let path = project.config_dir().join("config.toml");
+
+    let contents = match fs::read_to_string(&path) {
+      Ok(contents) => contents,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => {
+        debug!("No configuration found at {}", path.display());
+        return Ok(Self::default());
+      }
+      Err(e) => return Err(format!("Could not read {}: {e}", path.display()).into()),
+    };
+
+    debug!("Configuration file: {}", path.display());
+    let config: Self =
+      toml::from_str(&contents).map_err(|e| format!("Could not parse {}: {e}", path.display()))?;
+
+    if config.ratings.average > config.ratings.good {
+      return Err(
+        format!("Could not parse {}: ratings.average is above ratings.good", path.display()).into(),
+      );
+    }
+
+    Ok(config)
+  }
+}
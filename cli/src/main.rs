@@ -1,11 +1,24 @@
 #![warn(clippy::all)]
 
+mod columns;
+mod config;
+mod params;
 mod print;
-mod search;
+mod query;
+mod rpc;
+mod serve;
+mod shell;
+#[cfg(test)]
+mod testing;
 mod ui;
 
-use crate::print::{JsonPrinter, OutputFormat, Printer, TablePrinter, YamlPrinter};
-use crate::search::SearchRes;
+use crate::columns::Columns;
+use crate::config::Config;
+use crate::print::{
+  BatchResults, CsvPrinter, HtmlPrinter, JsonPrinter, MarkdownPrinter, NdjsonPrinter, OutputFormat, Printer,
+  RatingThresholds, ScanResults, TablePrinter, TitleDetails, YamlPrinter,
+};
+use crate::query::{parse_search_query, SearchQuery};
 use crate::ui::{create_progress_bar, create_progress_spinner};
 use atoi::atoi;
 use clap::Parser;
@@ -13,20 +26,24 @@ use derive_more::Display;
 use directories::ProjectDirs;
 use humantime::format_duration;
 use indicatif::ProgressBar;
-use log::{debug, error, log_enabled, warn};
+use log::{debug, error, info, warn};
 use regex::Regex;
 use reqwest::Url;
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashSet;
 use std::env;
 use std::error::Error;
-use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::ops::Bound;
 use std::path::{Path, PathBuf};
-use std::time::Instant;
-use tvrank::imdb::{Imdb, ImdbQuery, ImdbTitleId};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tvrank::imdb::{
+  Imdb, ImdbDatasets, ImdbFilter, ImdbGenre, ImdbKeywords, ImdbQuery, ImdbRatingPrior, ImdbResults,
+  ImdbSortDirection, ImdbSortKey, ImdbSortOrder, ImdbTitle, ImdbTitleId, ImdbTitleKind, ImdbTitleType,
+};
 use tvrank::title_info::TitleInfo;
+use tvrank::utils::random::Random;
 use tvrank::utils::result::Res;
 use walkdir::WalkDir;
 
@@ -35,12 +52,14 @@ use walkdir::WalkDir;
 enum TvRankErr {
   #[display(fmt = "Could not find cache directory")]
   CacheDir,
-  #[display(fmt = "Empty set of keywords")]
-  NoKeywords,
   #[display(fmt = "`{}` is not a directory", "_0.display()")]
   NotADirectory(PathBuf),
   #[display(fmt = "Id `{}` was not found", _0)]
   UnknownImdbId(String),
+  #[display(fmt = "Unknown genre `{}`", _0)]
+  UnknownGenre(String),
+  #[display(fmt = "Unknown title type `{}`", _0)]
+  UnknownTitleType(String),
 }
 
 impl TvRankErr {
@@ -48,10 +67,6 @@ impl TvRankErr {
     Err(Box::new(TvRankErr::CacheDir))
   }
 
-  fn no_keywords<T>() -> Res<T> {
-    Err(Box::new(TvRankErr::NoKeywords))
-  }
-
   fn not_a_directory<T>(path: PathBuf) -> Res<T> {
     Err(Box::new(TvRankErr::NotADirectory(path)))
   }
@@ -63,6 +78,9 @@ impl TvRankErr {
 
 impl Error for TvRankErr {}
 
+/// The number of titles listed when browsing, unless `--top` is given.
+const DEFAULT_TOP: usize = 25;
+
 fn parse_title_and_year(input: &str) -> Option<(&str, u16)> {
   let regex = match Regex::new(r"^(.+)\s+\((\d{4})\)$") {
     Ok(regex) => regex,
@@ -107,6 +125,14 @@ fn parse_title_and_year(input: &str) -> Option<(&str, u16)> {
   Some((title_match.as_str(), year_val))
 }
 
+fn parse_genre(input: &str) -> Result<ImdbGenre, TvRankErr> {
+  ImdbGenre::from_name(input).ok_or_else(|| TvRankErr::UnknownGenre(input.to_owned()))
+}
+
+fn parse_title_type(input: &str) -> Result<ImdbTitleType, TvRankErr> {
+  ImdbTitleType::from_name(input).ok_or_else(|| TvRankErr::UnknownTitleType(input.to_owned()))
+}
+
 #[derive(Debug, clap::Args)]
 struct GeneralOpts {
   /// Force updating internal databases
@@ -120,6 +146,16 @@ struct GeneralOpts {
   /// Verbose output (can be specified multiple times)
   #[clap(short, long, parse(from_occurrences))]
   verbose: u8,
+
+  /// Columns of the table output, as a comma-separated list of NAME[:WIDTH], where NAME is one
+  /// of title, original, year, rating, votes, runtime, genres, type, id or link (e.g.
+  /// "title:40,year,rating,link")
+  #[clap(long, value_name = "COLUMNS")]
+  columns: Option<Columns>,
+
+  /// Ratings at or above which titles are displayed in green or yellow, from the configuration
+  #[clap(skip)]
+  ratings: RatingThresholds,
 }
 
 #[derive(Debug, clap::Args)]
@@ -128,13 +164,155 @@ struct SearchOpts {
   #[clap(short = 'y', long)]
   sort_by_year: bool,
 
+  /// Rank by rating weighted by the number of votes, instead of the raw rating
+  #[clap(short, long)]
+  weighted: bool,
+
+  /// Sort by a comma-separated list of KEY[:asc|desc], where KEY is one of title, runtime,
+  /// votes, rating, weighted or year (e.g. "votes:desc,year:asc")
+  #[clap(short, long, value_name = "KEYS", conflicts_with_all = &["sort-by-year", "weighted"])]
+  sort: Option<ImdbSortOrder>,
+
+  /// Rating (out of 100) that weighted ratings of titles with few votes are pulled towards
+  #[clap(long, value_name = "RATING")]
+  prior_rating: Option<u8>,
+
+  /// Number of votes after which a title's own rating dominates its weighted rating
+  #[clap(long, value_name = "VOTES")]
+  prior_votes: Option<u32>,
+
   /// Only display the top N results
   #[clap(short, long, name = "N")]
   top: Option<usize>,
 
-  /// Set output format
-  #[clap(short, long, arg_enum, default_value = "table")]
-  output: OutputFormat,
+  /// Skip the first N results, e.g. to display the next page of results
+  #[clap(long, value_name = "N", default_value = "0")]
+  offset: usize,
+
+  /// Set output format [default: table]
+  #[clap(short, long, arg_enum)]
+  output: Option<OutputFormat>,
+}
+
+impl SearchOpts {
+  fn sort_order(&self) -> ImdbSortOrder {
+    let default_prior = ImdbRatingPrior::default();
+    let prior = ImdbRatingPrior::new(
+      self.prior_rating.unwrap_or_else(|| default_prior.rating()),
+      self.prior_votes.unwrap_or_else(|| default_prior.votes()),
+    );
+
+    if let Some(order) = &self.sort {
+      return order.clone().with_prior(prior);
+    }
+
+    let rating = if self.weighted {
+      ImdbSortKey::WeightedRating
+    } else {
+      ImdbSortKey::Rating
+    };
+
+    let keys = if self.sort_by_year {
+      vec![ImdbSortKey::Year, rating, ImdbSortKey::Title]
+    } else {
+      vec![rating, ImdbSortKey::Year, ImdbSortKey::Title]
+    };
+
+    ImdbSortOrder::new(keys.into_iter().map(|key| (key, ImdbSortDirection::Desc)).collect()).with_prior(prior)
+  }
+
+  fn output(&self) -> OutputFormat {
+    self.output.clone().unwrap_or(OutputFormat::Table)
+  }
+
+  /// Fill in the options that were not given on the command-line from the configuration.
+  fn with_config(mut self, config: &Config) -> Self {
+    if !self.has_sort_order() {
+      self.sort = config.sort.clone();
+    }
+
+    self.top = self.top.or(config.top);
+    self.output = self.output.or_else(|| config.output.clone());
+    self
+  }
+
+  fn has_sort_order(&self) -> bool {
+    self.sort.is_some() || self.sort_by_year || self.weighted
+  }
+
+  /// Whether the results are sorted, skipped or limited, in which case they can only be printed
+  /// once all of them are known.
+  fn is_ordered(&self) -> bool {
+    self.has_sort_order() || self.top.is_some() || self.offset > 0
+  }
+
+  fn results<'a>(&self, top: Option<usize>) -> ImdbResults<'a, 'a> {
+    let results = ImdbResults::new(self.sort_order()).with_offset(self.offset);
+
+    match top {
+      Some(top) => results.with_limit(top),
+      None => results,
+    }
+  }
+}
+
+#[derive(Debug, clap::Args)]
+struct FilterOpts {
+  /// Only include titles of GENRE (can be specified multiple times)
+  #[clap(short, long, value_name = "GENRE", parse(try_from_str = parse_genre))]
+  genre: Vec<ImdbGenre>,
+
+  /// Only include titles of TYPE, e.g. movie or tvSeries (can be specified multiple times)
+  #[clap(long = "type", value_name = "TYPE", parse(try_from_str = parse_title_type))]
+  title_type: Vec<ImdbTitleType>,
+
+  /// Only include titles released in or after YEAR
+  #[clap(long, value_name = "YEAR")]
+  min_year: Option<u16>,
+
+  /// Only include titles released in or before YEAR
+  #[clap(long, value_name = "YEAR")]
+  max_year: Option<u16>,
+
+  /// Only include titles rated at least RATING (out of 100)
+  #[clap(long, value_name = "RATING")]
+  min_rating: Option<u8>,
+
+  /// Only include titles with at least VOTES votes
+  #[clap(long, value_name = "VOTES")]
+  min_votes: Option<u32>,
+
+  /// Only include titles that are at least MINUTES long
+  #[clap(long, value_name = "MINUTES")]
+  min_runtime: Option<u16>,
+
+  /// Only include titles that are at most MINUTES long
+  #[clap(long, value_name = "MINUTES")]
+  max_runtime: Option<u16>,
+}
+
+impl FilterOpts {
+  fn to_filter(&self) -> ImdbFilter {
+    fn bounds<T>(min: Option<T>, max: Option<T>) -> (Bound<T>, Bound<T>) {
+      (min.map_or(Bound::Unbounded, Bound::Included), max.map_or(Bound::Unbounded, Bound::Included))
+    }
+
+    let mut filter = ImdbFilter::default()
+      .with_years(bounds(self.min_year, self.max_year))
+      .with_rating(bounds(self.min_rating, None))
+      .with_votes(bounds(self.min_votes, None))
+      .with_runtime(bounds(self.min_runtime, self.max_runtime));
+
+    for &genre in &self.genre {
+      filter = filter.with_genre(genre);
+    }
+
+    for &title_type in &self.title_type {
+      filter = filter.with_title_type(title_type);
+    }
+
+    filter
+  }
 }
 
 #[derive(Debug, clap::Parser)]
@@ -149,9 +327,13 @@ struct Opt {
 
 #[derive(Debug, clap::Subcommand)]
 enum Command {
-  /// Lookup a single title using "KEYWORDS" or "TITLE (YYYY)"
+  /// Lookup a single title using "KEYWORDS" or "TITLE (YYYY)", optionally with inline filters
   Search {
-    /// Search terms, as "KEYWORDS" or "TITLE (YYYY)"
+    /// Search terms, as "KEYWORDS" or "TITLE (YYYY)". Keywords can include quoted phrases
+    /// ("star wars"), exclusions (-lego) and alternatives (batman OR superman), and can be
+    /// followed by any filters such as
+    /// "genre:sci-fi", "type:movie", "year:1995..2005", "rating:>=70", "votes:>10000" or
+    /// "runtime:<120"
     #[clap(name = "TITLE")]
     title: String,
 
@@ -159,6 +341,78 @@ enum Command {
     #[clap(short, long)]
     exact: bool,
 
+    /// Display movies and series together, as a single list
+    #[clap(short, long)]
+    merge: bool,
+
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+
+    #[clap(flatten)]
+    search_opts: SearchOpts,
+  },
+
+  /// Lookup titles from FILE (or stdin), one "KEYWORDS" or "TITLE (YYYY)" query per line
+  Batch {
+    /// File of search queries, one per line, or "-" to read them from stdin (the default)
+    #[clap(name = "FILE")]
+    file: Option<PathBuf>,
+
+    /// Match the given titles exactly
+    #[clap(short, long)]
+    exact: bool,
+
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+
+    #[clap(flatten)]
+    search_opts: SearchOpts,
+  },
+
+  /// List the best rated titles matching the given filters, without any search terms
+  Top {
+    /// Only display movies
+    #[clap(long, conflicts_with = "series")]
+    movies: bool,
+
+    /// Only display series
+    #[clap(long)]
+    series: bool,
+
+    /// Display movies and series together, as a single list
+    #[clap(short, long, conflicts_with_all = &["movies", "series"])]
+    merge: bool,
+
+    #[clap(flatten)]
+    filter_opts: FilterOpts,
+
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+
+    #[clap(flatten)]
+    search_opts: SearchOpts,
+  },
+
+  /// Display every known detail of a single title
+  Info {
+    /// The unique IMDB ID ("ttXXXXX") or the IMDB URL of the title
+    #[clap(name = "IMDB-ID")]
+    id: String,
+
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+
+    /// Set output format [default: table]
+    #[clap(short, long, arg_enum)]
+    output: Option<OutputFormat>,
+  },
+
+  /// Suggest titles similar to a given title, most similar first unless a sort order is given
+  Similar {
+    /// The unique IMDB ID ("ttXXXXX" which can be found in the URL)
+    #[clap(name = "IMDB-ID")]
+    id: String,
+
     #[clap(flatten)]
     general_opts: GeneralOpts,
 
@@ -166,6 +420,66 @@ enum Command {
     search_opts: SearchOpts,
   },
 
+  /// Pick random titles matching the given filters, e.g. for a movie night
+  Random {
+    /// Only pick movies
+    #[clap(long, conflicts_with = "series")]
+    movies: bool,
+
+    /// Only pick series
+    #[clap(long)]
+    series: bool,
+
+    /// Number of titles to pick
+    #[clap(short = 'n', long, value_name = "N", default_value = "1")]
+    count: usize,
+
+    /// Seed of the random picks, to pick the same titles again (a new seed is used otherwise)
+    #[clap(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Only pick from the movies found in DIR, as with scan-movies
+    #[clap(long, value_name = "DIR", conflicts_with = "series")]
+    dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    filter_opts: FilterOpts,
+
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+
+    /// Set output format [default: table]
+    #[clap(short, long, arg_enum)]
+    output: Option<OutputFormat>,
+  },
+
+  /// Start an interactive shell to run queries against a database that is only loaded once
+  Shell {
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+
+    #[clap(flatten)]
+    search_opts: SearchOpts,
+  },
+
+  /// Serve search, lookup, top and scan queries as JSON over HTTP, with the database only
+  /// loaded once
+  Serve {
+    /// Address to listen on
+    #[clap(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    bind: String,
+
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+  },
+
+  /// Answer line-delimited JSON-RPC requests from stdin on stdout, with the database only
+  /// loaded once
+  Rpc {
+    #[clap(flatten)]
+    general_opts: GeneralOpts,
+  },
+
   /// Lookup movie titles from a directory
   ScanMovies {
     /// Directory of movie folders named "TITLE (YYYY)"
@@ -215,83 +529,314 @@ fn display_title_and_year(title: &str, year: u16) -> String {
   format!("{} ({})", title, year)
 }
 
-fn display_keywords(keywords: &[&str]) -> String {
-  keywords.join(", ")
+fn imdb_title_results<'a>(
+  imdb: &'a Imdb,
+  (title, year): (&str, Option<u16>),
+  keywords: Option<&ImdbKeywords>,
+  filter: &ImdbFilter,
+  kind: ImdbQuery,
+  search_opts: &SearchOpts,
+) -> ImdbResults<'a, 'a> {
+  let mut results = search_opts.results(search_opts.top);
+  // With a limit, only the best titles needed for the page are kept while searching.
+  let n = search_opts.top.map(|top| search_opts.offset + top);
+  let order = results.order().clone();
+
+  match (keywords, year, n) {
+    (None, _, Some(n)) => results.extend_top(imdb.top_by_title(title, year, filter, &order, kind, n)),
+    (Some(keywords), _, Some(n)) => {
+      results.extend_top(imdb.top_by_keywords(keywords, year, filter, &order, kind, n))
+    }
+    (None, Some(year), None) => results.extend_filtered(imdb.by_title_and_year(title, year, kind), filter),
+    (None, None, None) => results.extend_filtered(imdb.by_title(title, kind), filter),
+    (Some(keywords), Some(year), None) => {
+      results.extend_filtered(imdb.by_keywords_and_year(keywords, year, kind), filter)
+    }
+    (Some(keywords), None, None) => results.extend_filtered(imdb.by_keywords(keywords, kind), filter),
+  }
+
+  results
 }
 
-fn create_keywords_set(title: &str) -> Res<Vec<&str>> {
-  debug!("Going to use `{}` as keywords for search query", title);
+fn imdb_search_results<'a>(
+  imdb: &'a Imdb,
+  query: &SearchQuery,
+  exact: bool,
+  kind: ImdbQuery,
+  search_opts: &SearchOpts,
+) -> Res<(ImdbResults<'a, 'a>, Option<String>)> {
+  let filter = &query.filter;
 
-  let set: HashSet<_> = title.split_whitespace().collect();
-  let set: HashSet<_> = if set.is_empty() {
-    return TvRankErr::no_keywords();
-  } else if set.len() > 1 {
-    set.into_iter().filter(|kw| kw.len() > 1).collect()
+  if query.terms.is_empty() && query.has_filters {
+    return Ok((imdb_top_results(imdb, filter, kind, search_opts), None));
+  }
+
+  let (title, year) = match parse_title_and_year(&query.terms) {
+    Some((title, year)) => (title, Some(year)),
+    None => (query.terms.as_str(), None),
+  };
+
+  let keywords = if exact {
+    None
   } else {
-    set
+    Some(ImdbKeywords::parse(title)?)
   };
 
-  let keywords: Vec<&str> = set.into_iter().collect();
+  let results = imdb_title_results(imdb, (title, year), keywords.as_ref(), filter, kind, search_opts);
 
-  if log_enabled!(log::Level::Debug) {
-    debug!("Keywords: {}", display_keywords(&keywords));
-  }
+  let search_terms = match (year, keywords) {
+    (Some(year), _) => display_title_and_year(title, year),
+    (None, Some(keywords)) => keywords.to_string(),
+    (None, None) => title.to_owned(),
+  };
 
-  Ok(keywords)
+  Ok((results, Some(search_terms)))
 }
 
-fn imdb_title<'a>(
+fn imdb_title(
   title: &str,
-  imdb: &'a Imdb,
+  imdb: &Imdb,
   imdb_url: &Url,
   search_opts: &SearchOpts,
   exact: bool,
+  merge: bool,
   printer: Box<dyn Printer>,
 ) -> Res<()> {
-  let mut movies_results = SearchRes::new(search_opts.sort_by_year, search_opts.top);
-  let mut series_results = SearchRes::new(search_opts.sort_by_year, search_opts.top);
-
-  let search_terms = if let Some((title, year)) = parse_title_and_year(title) {
-    let lc_title = title.to_lowercase();
-    if exact {
-      movies_results.extend(imdb.by_title_and_year(&lc_title, year, ImdbQuery::Movies));
-      series_results.extend(imdb.by_title_and_year(&lc_title, year, ImdbQuery::Series));
-    } else {
-      let keywords = create_keywords_set(&lc_title)?;
-      movies_results.extend(imdb.by_keywords_and_year(&keywords, year, ImdbQuery::Movies));
-      series_results.extend(imdb.by_keywords_and_year(&keywords, year, ImdbQuery::Series));
-    }
+  let query = parse_search_query(title)?;
 
-    Some(display_title_and_year(title, year))
+  if merge {
+    let (results, search_terms) = imdb_search_results(imdb, &query, exact, ImdbQuery::All, search_opts)?;
+    printer.print_merged(results, imdb_url, search_terms.as_deref())?;
   } else {
-    let lc_title = title.to_lowercase();
-    if exact {
-      movies_results.extend(imdb.by_title(&lc_title, ImdbQuery::Movies));
-      series_results.extend(imdb.by_title(&lc_title, ImdbQuery::Series));
-      Some(lc_title)
-    } else {
-      let keywords = create_keywords_set(&lc_title)?;
-      movies_results.extend(imdb.by_keywords(&keywords, ImdbQuery::Movies));
-      series_results.extend(imdb.by_keywords(&keywords, ImdbQuery::Series));
-      Some(display_keywords(&keywords))
+    let (movies_results, search_terms) =
+      imdb_search_results(imdb, &query, exact, ImdbQuery::Movies, search_opts)?;
+    let (series_results, _) = imdb_search_results(imdb, &query, exact, ImdbQuery::Series, search_opts)?;
+    printer.print(Some(movies_results), Some(series_results), imdb_url, search_terms.as_deref())?;
+  }
+
+  Ok(())
+}
+
+fn imdb_batch(
+  file: Option<&Path>,
+  imdb: &Imdb,
+  imdb_url: &Url,
+  search_opts: &SearchOpts,
+  exact: bool,
+  printer: Box<dyn Printer>,
+) -> Res<()> {
+  let input: Box<dyn BufRead> = match file {
+    Some(file) if file != Path::new("-") => Box::new(BufReader::new(File::open(file)?)),
+    _ => Box::new(BufReader::new(io::stdin())),
+  };
+
+  let mut batch = BatchResults { matched: Vec::new(), unmatched: Vec::new() };
+
+  for line in input.lines() {
+    let line = line?;
+    let line = line.trim();
+
+    if line.is_empty() {
+      continue;
     }
+
+    let results = parse_search_query(line)
+      .map_err(Box::from)
+      .and_then(|query| imdb_search_results(imdb, &query, exact, ImdbQuery::All, search_opts));
+
+    match results {
+      Ok((results, _)) if !results.is_empty() => batch.matched.push((line.to_owned(), results)),
+      Ok(_) => batch.unmatched.push(line.to_owned()),
+      Err(e) => {
+        warn!("Could not search for `{line}`: {e}");
+        batch.unmatched.push(line.to_owned());
+      }
+    }
+  }
+
+  printer.print_batch(batch, imdb_url)
+}
+
+fn imdb_top_results<'a>(
+  imdb: &'a Imdb,
+  filter: &ImdbFilter,
+  query: ImdbQuery,
+  search_opts: &SearchOpts,
+) -> ImdbResults<'a, 'a> {
+  let top = search_opts.top.unwrap_or(DEFAULT_TOP);
+  let mut results = search_opts.results(Some(top));
+  let top = imdb.top(filter, results.order(), query, search_opts.offset + top);
+  results.extend_top(top);
+  results
+}
+
+fn imdb_top(
+  imdb: &Imdb,
+  imdb_url: &Url,
+  filter_opts: &FilterOpts,
+  search_opts: &SearchOpts,
+  (movies, series, merge): (bool, bool, bool),
+  printer: Box<dyn Printer>,
+) -> Res<()> {
+  let filter = filter_opts.to_filter();
+
+  if merge {
+    let results = imdb_top_results(imdb, &filter, ImdbQuery::All, search_opts);
+    printer.print_merged(results, imdb_url, None)?;
+    return Ok(());
+  }
+
+  let movies_results = if series {
+    None
+  } else {
+    Some(imdb_top_results(imdb, &filter, ImdbQuery::Movies, search_opts))
+  };
+
+  let series_results = if movies {
+    None
+  } else {
+    Some(imdb_top_results(imdb, &filter, ImdbQuery::Series, search_opts))
   };
 
-  printer.print(Some(movies_results), Some(series_results), imdb_url, search_terms.as_deref())?;
+  printer.print(movies_results, series_results, imdb_url, None)?;
 
   Ok(())
 }
 
-fn imdb_movies_dir(
-  dir: &Path,
+/// Return the IMDB ID in an IMDB URL (e.g. "https://www.imdb.com/title/tt0133093/"), or the
+/// input itself if it is not one.
+fn parse_imdb_id(input: &str) -> &str {
+  let input = input.trim();
+
+  match input.split_once("/title/") {
+    Some((_, path)) => path.split(['/', '?', '#']).next().unwrap_or(path),
+    None => input,
+  }
+}
+
+fn imdb_info(id: &str, imdb: &Imdb, imdb_url: &Url, printer: Box<dyn Printer>) -> Res<()> {
+  let id = parse_imdb_id(id);
+  let title_id = ImdbTitleId::try_from(id)?;
+
+  let title = match imdb.by_id(&title_id, ImdbQuery::All) {
+    Some(title) => title,
+    None => return TvRankErr::unknown_imdb_id(id.to_owned()),
+  };
+
+  printer.print_info(&TitleDetails::new(title, imdb_url, &ImdbRatingPrior::default())?)
+}
+
+fn imdb_similar(
+  id: &str,
   imdb: &Imdb,
   imdb_url: &Url,
   search_opts: &SearchOpts,
   printer: Box<dyn Printer>,
 ) -> Res<()> {
-  let mut at_least_one = false;
-  let mut at_least_one_matched = false;
-  let mut results = SearchRes::new(search_opts.sort_by_year, search_opts.top);
+  let title_id = ImdbTitleId::try_from(id)?;
+
+  let title = match imdb.by_id(&title_id, ImdbQuery::All) {
+    Some(title) => title,
+    None => return TvRankErr::unknown_imdb_id(id.to_owned()),
+  };
+
+  let top = search_opts.top.unwrap_or(DEFAULT_TOP);
+  let mut results = search_opts.results(Some(top));
+  let similar = imdb.similar_to(&title_id, search_opts.sort_order().prior(), search_opts.offset + top);
+
+  if search_opts.has_sort_order() {
+    results.extend(similar);
+  } else {
+    results.extend_ranked(similar);
+  }
+
+  let search_terms = match title.start_year() {
+    Some(year) => format!("similar to {}", display_title_and_year(title.primary_title(), year)),
+    None => format!("similar to {}", title.primary_title()),
+  };
+
+  match title.kind() {
+    ImdbTitleKind::Movie => printer.print(Some(results), None, imdb_url, Some(&search_terms))?,
+    ImdbTitleKind::Series => printer.print(None, Some(results), imdb_url, Some(&search_terms))?,
+  }
+
+  Ok(())
+}
+
+fn random_seed() -> u64 {
+  match SystemTime::now().duration_since(UNIX_EPOCH) {
+    Ok(duration) => duration.as_nanos() as u64,
+    Err(_) => 0,
+  }
+}
+
+fn imdb_movies_in_dir<'a>(dir: &Path, imdb: &'a Imdb) -> Res<ImdbResults<'a, 'a>> {
+  let results = ImdbResults::new(ImdbSortOrder::default());
+  let scan = imdb_movies_dir_results(dir, imdb, results, true, &mut |_, _| Ok(false))?;
+
+  for (matches, search_terms) in scan.unmatched {
+    if matches.is_empty() {
+      warn!("Skipping `{search_terms}` because it does not match any title");
+    } else {
+      warn!("Skipping `{search_terms}` because it matches more than one title");
+    }
+  }
+
+  Ok(scan.results)
+}
+
+fn imdb_random(
+  imdb: &Imdb,
+  imdb_url: &Url,
+  filter_opts: &FilterOpts,
+  (movies, series): (bool, bool),
+  (count, seed): (usize, Option<u64>),
+  dir: Option<&Path>,
+  printer: Box<dyn Printer>,
+) -> Res<()> {
+  let seed = seed.unwrap_or_else(random_seed);
+
+  // The seed is only worth printing along with titles meant to be read, not parsed.
+  if matches!(printer.get_format(), OutputFormat::Table) {
+    eprintln!("Random seed: {seed}");
+  } else {
+    info!("Random seed: {seed}");
+  }
+
+  let mut random = Random::new(seed);
+  let filter = filter_opts.to_filter();
+
+  let titles = if let Some(dir) = dir {
+    let titles = imdb_movies_in_dir(dir, imdb)?;
+    random.sample(titles.into_iter().filter(|title| filter.matches(title)), count)
+  } else {
+    let query = if movies {
+      ImdbQuery::Movies
+    } else if series {
+      ImdbQuery::Series
+    } else {
+      ImdbQuery::All
+    };
+
+    imdb.random(&filter, query, count, &mut random)
+  };
+
+  let mut results = ImdbResults::new(ImdbSortOrder::default());
+  results.extend_ranked(titles);
+  printer.print_merged(results, imdb_url, None)?;
+
+  Ok(())
+}
+
+fn imdb_movies_dir_results<'a>(
+  dir: &Path,
+  imdb: &'a Imdb,
+  results: ImdbResults<'a, 'a>,
+  separate: bool,
+  scanned: &mut dyn FnMut(&ImdbTitle, &Path) -> Res<bool>,
+) -> Res<ScanResults<'a>> {
+  let sort_order = results.order().clone();
+  let mut results = ScanResults::new(results);
   let walkdir = WalkDir::new(dir).min_depth(1);
 
   for entry in walkdir {
@@ -302,8 +847,7 @@ fn imdb_movies_dir(
 
       if let Ok(title_info) = TitleInfo::from_path(entry_path) {
         if let Some(result) = imdb.by_id(title_info.imdb().id(), ImdbQuery::Movies) {
-          at_least_one_matched = true;
-          results.push(result);
+          results.push(result, entry_path, scanned)?;
           continue;
         } else {
           let id = title_info.imdb().id();
@@ -316,30 +860,17 @@ fn imdb_movies_dir(
         let filename = filename.to_string_lossy();
 
         if let Some((title, year)) = parse_title_and_year(&filename) {
-          at_least_one = true;
-
-          let mut local_results = SearchRes::new(search_opts.sort_by_year, None);
-          local_results.extend(imdb.by_title_and_year(&title.to_lowercase(), year, ImdbQuery::Movies));
-
-          if local_results.is_empty() || local_results.len() > 1 {
-            if local_results.len() > 1 {
-              at_least_one_matched = true;
-            }
-
-            if matches!(printer.get_format(), OutputFormat::Table) {
-              printer.print(
-                Some(local_results),
-                None,
-                imdb_url,
-                Some(&display_title_and_year(title, year)),
-              )?;
-            } else {
-              results.extend(local_results);
-            }
-          } else {
-            at_least_one_matched = true;
-            results.extend(local_results);
-          }
+          results.at_least_one = true;
+
+          let mut local_results = ImdbResults::new(sort_order.clone());
+          local_results.extend(imdb.by_title_and_year(title, year, ImdbQuery::Movies));
+          results.add(
+            local_results,
+            entry_path,
+            || display_title_and_year(title, year),
+            separate,
+            scanned,
+          )?;
         } else {
           warn!(
             "Skipping `{}` because `{}` does not follow the TITLE (YYYY) format",
@@ -353,19 +884,30 @@ fn imdb_movies_dir(
     }
   }
 
-  if !at_least_one {
-    eprintln!("No valid directory names");
-    return Ok(());
-  }
-
-  if !at_least_one_matched {
-    eprintln!("None of the directories matched any titles");
-    return Ok(());
-  }
-
-  printer.print(Some(results), None, imdb_url, None)?;
+  Ok(results)
+}
 
-  Ok(())
+fn imdb_movies_dir(
+  dir: &Path,
+  imdb: &Imdb,
+  imdb_url: &Url,
+  search_opts: &SearchOpts,
+  printer: Box<dyn Printer>,
+) -> Res<()> {
+  let separate =
+    matches!(printer.get_format(), OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html);
+  let prior = *search_opts.sort_order().prior();
+  let stream = !search_opts.is_ordered();
+  let mut scanned = |title: &ImdbTitle, dir: &Path| {
+    if stream {
+      printer.print_scanned(title, dir, &prior)
+    } else {
+      Ok(false)
+    }
+  };
+  let results =
+    imdb_movies_dir_results(dir, imdb, search_opts.results(search_opts.top), separate, &mut scanned)?;
+  printer.print_scan(results, imdb_url, ImdbQuery::Movies)
 }
 
 fn imdb_mark(dir: &Path, id: &str, imdb: &Imdb, force: bool) -> Res<()> {
@@ -410,16 +952,15 @@ fn imdb_mark(dir: &Path, id: &str, imdb: &Imdb, force: bool) -> Res<()> {
   Ok(())
 }
 
-fn imdb_series_dir(
+fn imdb_series_dir_results<'a>(
   dir: &Path,
-  imdb: &Imdb,
-  imdb_url: &Url,
-  search_opts: &SearchOpts,
-  printer: Box<dyn Printer>,
-) -> Res<()> {
-  let mut at_least_one = false;
-  let mut at_least_one_matched = false;
-  let mut results = SearchRes::new(search_opts.sort_by_year, search_opts.top);
+  imdb: &'a Imdb,
+  results: ImdbResults<'a, 'a>,
+  separate: bool,
+  scanned: &mut dyn FnMut(&ImdbTitle, &Path) -> Res<bool>,
+) -> Res<ScanResults<'a>> {
+  let sort_order = results.order().clone();
+  let mut results = ScanResults::new(results);
   let walkdir = WalkDir::new(dir).min_depth(1).max_depth(1);
 
   for entry in walkdir {
@@ -430,8 +971,7 @@ fn imdb_series_dir(
 
       if let Ok(title_info) = TitleInfo::from_path(entry_path) {
         if let Some(result) = imdb.by_id(title_info.imdb().id(), ImdbQuery::Series) {
-          at_least_one_matched = true;
-          results.push(result);
+          results.push(result, entry_path, scanned)?;
           continue;
         } else {
           let id = title_info.imdb().id();
@@ -441,50 +981,48 @@ fn imdb_series_dir(
       }
 
       if let Some(filename) = entry_path.file_name() {
-        at_least_one = true;
+        results.at_least_one = true;
 
         let filename = filename.to_string_lossy();
-        let mut local_results = SearchRes::new(search_opts.sort_by_year, None);
+        let mut local_results = ImdbResults::new(sort_order.clone());
 
         let search_terms = if let Some((title, year)) = parse_title_and_year(&filename) {
-          local_results.extend(imdb.by_title_and_year(&title.to_lowercase(), year, ImdbQuery::Series));
+          local_results.extend(imdb.by_title_and_year(title, year, ImdbQuery::Series));
           Cow::from(display_title_and_year(title, year))
         } else {
-          local_results.extend(imdb.by_title(&filename.to_lowercase(), ImdbQuery::Series));
+          local_results.extend(imdb.by_title(&filename, ImdbQuery::Series));
           filename
         };
 
-        if local_results.is_empty() || local_results.len() > 1 {
-          if local_results.len() > 1 {
-            at_least_one_matched = true;
-          }
-
-          if matches!(printer.get_format(), OutputFormat::Table) {
-            printer.print(None, Some(local_results), imdb_url, Some(&search_terms))?;
-          } else {
-            results.extend(local_results);
-          }
-        } else {
-          at_least_one_matched = true;
-          results.extend(local_results);
-        }
+        results.add(local_results, entry_path, || search_terms.into_owned(), separate, scanned)?;
       }
     }
   }
 
-  if !at_least_one {
-    eprintln!("No valid directory names");
-    return Ok(());
-  }
-
-  if !at_least_one_matched {
-    eprintln!("None of the directories matched any titles");
-    return Ok(());
-  }
-
-  printer.print(None, Some(results), imdb_url, None)?;
+  Ok(results)
+}
 
-  Ok(())
+fn imdb_series_dir(
+  dir: &Path,
+  imdb: &Imdb,
+  imdb_url: &Url,
+  search_opts: &SearchOpts,
+  printer: Box<dyn Printer>,
+) -> Res<()> {
+  let separate =
+    matches!(printer.get_format(), OutputFormat::Table | OutputFormat::Markdown | OutputFormat::Html);
+  let prior = *search_opts.sort_order().prior();
+  let stream = !search_opts.is_ordered();
+  let mut scanned = |title: &ImdbTitle, dir: &Path| {
+    if stream {
+      printer.print_scanned(title, dir, &prior)
+    } else {
+      Ok(false)
+    }
+  };
+  let results =
+    imdb_series_dir_results(dir, imdb, search_opts.results(search_opts.top), separate, &mut scanned)?;
+  printer.print_scan(results, imdb_url, ImdbQuery::Series)
 }
 
 fn create_project() -> Res<ProjectDirs> {
@@ -496,9 +1034,16 @@ fn create_project() -> Res<ProjectDirs> {
   }
 }
 
-fn create_cache_dir(project: &ProjectDirs) -> Res<&Path> {
-  let app_cache_dir = project.cache_dir();
-  fs::create_dir_all(app_cache_dir)?;
+fn create_cache_dir(project: &ProjectDirs, config: &Config) -> Res<PathBuf> {
+  let app_cache_dir = match &config.cache_dir {
+    Some(cache_dir) => cache_dir.clone(),
+    None => {
+      // Only the default cache directory is owned by TVrank, a configured one may hold anything.
+      Imdb::remove_legacy_cache(project.cache_dir());
+      project.cache_dir().to_path_buf()
+    }
+  };
+  fs::create_dir_all(&app_cache_dir)?;
   debug!("Cache directory: {}", app_cache_dir.display());
   Ok(app_cache_dir)
 }
@@ -512,15 +1057,38 @@ fn get_imdb_url() -> Res<Url> {
 fn create_output_printer(output_format: &OutputFormat, general_opts: &GeneralOpts) -> Box<dyn Printer> {
   match output_format {
     OutputFormat::Json => Box::new(JsonPrinter::new()),
-    OutputFormat::Table => Box::new(TablePrinter::new(general_opts.color)),
+    OutputFormat::Table => Box::new(TablePrinter::new(
+      general_opts.color,
+      general_opts.columns.clone().unwrap_or_default(),
+      general_opts.ratings,
+    )),
     OutputFormat::Yaml => Box::new(YamlPrinter::new()),
+    OutputFormat::Csv => Box::new(CsvPrinter::new(b',')),
+    OutputFormat::Tsv => Box::new(CsvPrinter::new(b'\t')),
+    OutputFormat::Markdown => Box::new(MarkdownPrinter::new(general_opts.ratings)),
+    OutputFormat::Html => Box::new(HtmlPrinter::new(general_opts.ratings)),
+    OutputFormat::Ndjson => Box::new(NdjsonPrinter::new()),
   }
 }
 
-fn create_imdb_service(app_cache_dir: &Path, force_update: bool) -> Res<Imdb> {
+fn create_datasets(force_update: bool, config: &Config) -> ImdbDatasets {
+  let mut datasets = ImdbDatasets::default().with_force_update(force_update);
+
+  if let Some(url) = &config.dataset_url {
+    datasets = datasets.with_url(url.clone());
+  }
+
+  if let Some(refresh_interval) = config.refresh_interval {
+    datasets = datasets.with_max_age(refresh_interval.into());
+  }
+
+  datasets
+}
+
+fn create_imdb_service(app_cache_dir: &Path, datasets: &ImdbDatasets) -> Res<Imdb> {
   let start_time = Instant::now();
   let progress_bar: RefCell<Option<ProgressBar>> = RefCell::new(None);
-  let imdb = Imdb::new(app_cache_dir, force_update, &|content_len: Option<u64>, delta| {
+  let imdb = Imdb::with_datasets(app_cache_dir, datasets, &|content_len: Option<u64>, delta| {
     let mut progress_bar_mut = progress_bar.borrow_mut();
     match &*progress_bar_mut {
       Some(bar) => bar.inc(delta),
@@ -552,12 +1120,24 @@ fn is_no_color_env_set() -> bool {
 fn merge_general_opts(locals: GeneralOpts, globals: GeneralOpts) -> GeneralOpts {
   GeneralOpts {
     force_update: locals.force_update || globals.force_update,
-    color: !is_no_color_env_set() || locals.color || globals.color,
+    color: locals.color || globals.color,
     verbose: if locals.verbose > 0 {
       locals.verbose
     } else {
       globals.verbose
     },
+    columns: locals.columns.or(globals.columns),
+    ratings: globals.ratings,
+  }
+}
+
+/// Fill in the options that were not given on the command-line from the configuration.
+fn apply_config(general_opts: GeneralOpts, config: &Config) -> GeneralOpts {
+  GeneralOpts {
+    color: general_opts.color || config.color.unwrap_or_else(|| !is_no_color_env_set()),
+    columns: general_opts.columns.or_else(|| config.table.columns.clone()),
+    ratings: config.ratings,
+    ..general_opts
   }
 }
 
@@ -596,6 +1176,8 @@ macro_rules! fail {
 
 struct Context {
   general_opts: GeneralOpts,
+  config: Config,
+  app_cache_dir: PathBuf,
   have_logger: bool,
   imdb_url: Url,
   service: Imdb,
@@ -618,11 +1200,14 @@ impl Context {
     // trace!("Trace output enabled.");
 
     let project = fail!(have_logger, create_project());
-    let app_cache_dir = fail!(have_logger, create_cache_dir(&project));
+    let config = fail!(have_logger, Config::load(&project));
+    let general_opts = apply_config(general_opts, &config);
+    let app_cache_dir = fail!(have_logger, create_cache_dir(&project, &config));
     let imdb_url = fail!(have_logger, get_imdb_url());
-    let service = fail!(have_logger, create_imdb_service(app_cache_dir, general_opts.force_update));
+    let datasets = create_datasets(general_opts.force_update, &config);
+    let service = fail!(have_logger, create_imdb_service(&app_cache_dir, &datasets));
 
-    Self { general_opts, have_logger, imdb_url, service }
+    Self { general_opts, config, app_cache_dir, have_logger, imdb_url, service }
   }
 
   fn destroy(self) {
@@ -635,19 +1220,99 @@ fn main() {
   let args = Opt::parse();
 
   match args.command {
-    Command::Search { title, exact, general_opts, search_opts } => {
+    Command::Search { title, exact, merge, general_opts, search_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let printer = create_output_printer(&search_opts.output(), &context.general_opts);
+      let start_time = Instant::now();
+      fail!(context.have_logger, imdb_title(&title, &context.service, &context.imdb_url, &search_opts, exact, merge, printer) => {
+        context.destroy();
+      });
+      debug!("IMDB query took {}", format_duration(Instant::now().duration_since(start_time)));
+      context.destroy();
+    }
+    Command::Batch { file, exact, general_opts, search_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let printer = create_output_printer(&search_opts.output(), &context.general_opts);
+      let start_time = Instant::now();
+      fail!(context.have_logger, imdb_batch(file.as_deref(), &context.service, &context.imdb_url, &search_opts, exact, printer) => {
+        context.destroy();
+      });
+      debug!("IMDB query took {}", format_duration(Instant::now().duration_since(start_time)));
+      context.destroy();
+    }
+    Command::Top { movies, series, merge, filter_opts, general_opts, search_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let printer = create_output_printer(&search_opts.output(), &context.general_opts);
+      let start_time = Instant::now();
+      fail!(context.have_logger, imdb_top(&context.service, &context.imdb_url, &filter_opts, &search_opts, (movies, series, merge), printer) => {
+        context.destroy();
+      });
+      debug!("IMDB query took {}", format_duration(Instant::now().duration_since(start_time)));
+      context.destroy();
+    }
+    Command::Info { id, general_opts, output } => {
+      let context = Context::new(general_opts, args.general_opts);
+      let output = output.or_else(|| context.config.output.clone()).unwrap_or(OutputFormat::Table);
+      let printer = create_output_printer(&output, &context.general_opts);
+      let start_time = Instant::now();
+      fail!(context.have_logger, imdb_info(&id, &context.service, &context.imdb_url, printer) => {
+        context.destroy();
+      });
+      debug!("IMDB query took {}", format_duration(Instant::now().duration_since(start_time)));
+      context.destroy();
+    }
+    Command::Similar { id, general_opts, search_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let printer = create_output_printer(&search_opts.output(), &context.general_opts);
+      let start_time = Instant::now();
+      fail!(context.have_logger, imdb_similar(&id, &context.service, &context.imdb_url, &search_opts, printer) => {
+        context.destroy();
+      });
+      debug!("IMDB query took {}", format_duration(Instant::now().duration_since(start_time)));
+      context.destroy();
+    }
+    Command::Random { movies, series, count, seed, dir, filter_opts, general_opts, output } => {
       let context = Context::new(general_opts, args.general_opts);
-      let printer = create_output_printer(&search_opts.output, &context.general_opts);
+      let output = output.or_else(|| context.config.output.clone()).unwrap_or(OutputFormat::Table);
+      let printer = create_output_printer(&output, &context.general_opts);
       let start_time = Instant::now();
-      fail!(context.have_logger, imdb_title(&title, &context.service, &context.imdb_url, &search_opts, exact, printer) => {
+      fail!(context.have_logger, imdb_random(&context.service, &context.imdb_url, &filter_opts, (movies, series), (count, seed), dir.as_deref(), printer) => {
         context.destroy();
       });
       debug!("IMDB query took {}", format_duration(Instant::now().duration_since(start_time)));
       context.destroy();
     }
+    Command::Shell { general_opts, search_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let history = context.app_cache_dir.join("shell_history");
+      fail!(context.have_logger, shell::run(&context.service, &context.imdb_url, &context.general_opts, search_opts, &history) => {
+        context.destroy();
+      });
+      context.destroy();
+    }
+    Command::Serve { bind, general_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      fail!(context.have_logger, serve::run(&context.service, &bind) => {
+        context.destroy();
+      });
+      context.destroy();
+    }
+    Command::Rpc { general_opts } => {
+      let context = Context::new(general_opts, args.general_opts);
+      fail!(context.have_logger, rpc::run(&context.service) => {
+        context.destroy();
+      });
+      context.destroy();
+    }
     Command::ScanMovies { dir, general_opts, search_opts } => {
       let context = Context::new(general_opts, args.general_opts);
-      let printer = create_output_printer(&search_opts.output, &context.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let printer = create_output_printer(&search_opts.output(), &context.general_opts);
       let start_time = Instant::now();
       fail!(context.have_logger, imdb_movies_dir(&dir, &context.service, &context.imdb_url, &search_opts, printer) => {
         context.destroy();
@@ -657,7 +1322,8 @@ fn main() {
     }
     Command::ScanSeries { dir, general_opts, search_opts } => {
       let context = Context::new(general_opts, args.general_opts);
-      let printer = create_output_printer(&search_opts.output, &context.general_opts);
+      let search_opts = search_opts.with_config(&context.config);
+      let printer = create_output_printer(&search_opts.output(), &context.general_opts);
       let start_time = Instant::now();
       fail!(context.have_logger, imdb_series_dir(&dir, &context.service, &context.imdb_url, &search_opts, printer) => {
         context.destroy();
@@ -678,3 +1344,22 @@ fn main() {
 
   eprintln!("Total time: {}", format_duration(Instant::now().duration_since(start_time)));
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::parse_imdb_id;
+
+  #[test]
+  fn test_parse_imdb_id() {
+    assert_eq!(parse_imdb_id("tt0133093"), "tt0133093");
+    assert_eq!(parse_imdb_id("  tt0133093\n"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title/tt0133093"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title/tt0133093/"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title/tt0133093/?ref_=nv_sr_1"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title/tt0133093?ref_=nv_sr_1"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title/tt0133093#cast"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://m.imdb.com/title/tt0133093/reviews"), "tt0133093");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title/"), "");
+    assert_eq!(parse_imdb_id("https://www.imdb.com/title//"), "");
+  }
+}
@@ -0,0 +1,145 @@
+#![warn(clippy::all)]
+
+use crate::print::{to_json, to_json_merged};
+use crate::{FilterOpts, SearchOpts};
+use tvrank::imdb::{ImdbQuery, ImdbResults};
+use tvrank::utils::result::Res;
+
+/// Parse named parameters, e.g. of an HTTP or RPC request, as the command-line options of `P`.
+///
+/// Each parameter is passed as `--name=value`, with underscores in `name` replaced by dashes,
+/// and parameters without a value are passed as flags, so that requests accept the same
+/// options as the corresponding subcommands. Values are attached to their names so that values
+/// starting with a dash (e.g. `-sequel alien`) are not mistaken for options.
+///
+/// # Arguments
+///
+/// * `params` - The names and values of the parameters.
+pub fn parse_params<P: clap::Parser>(params: impl IntoIterator<Item = (String, Option<String>)>) -> Res<P> {
+  let mut args = vec![String::from("tvrank")];
+
+  for (name, value) in params {
+    let name = name.replace('_', "-");
+
+    match value {
+      Some(value) => args.push(format!("--{name}={value}")),
+      None => args.push(format!("--{name}")),
+    }
+  }
+
+  match P::try_parse_from(args) {
+    Ok(params) => Ok(params),
+    Err(e) => {
+      // Only keep the error itself, without the usage information that follows it.
+      let e = e.to_string();
+      let e = e
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ");
+      Err(e.strip_prefix("error: ").unwrap_or(&e).into())
+    }
+  }
+}
+
+/// Parameters selecting, filtering and sorting movies and series.
+#[derive(Debug, clap::Parser)]
+pub struct QueryParams {
+  /// Only return movies
+  #[clap(long, conflicts_with = "series")]
+  pub movies: bool,
+
+  /// Only return series
+  #[clap(long)]
+  pub series: bool,
+
+  /// Return movies and series together, as a single list
+  #[clap(long, conflicts_with_all = &["movies", "series"])]
+  pub merge: bool,
+
+  #[clap(flatten)]
+  pub filter_opts: FilterOpts,
+
+  #[clap(flatten)]
+  pub search_opts: SearchOpts,
+}
+
+impl QueryParams {
+  /// Run a query for the selected kinds of titles and return the results as JSON, as
+  /// displayed by `JsonPrinter`.
+  ///
+  /// # Arguments
+  ///
+  /// * `query` - Runs the query for the given kind of titles.
+  pub fn to_json<'a>(&self, query: impl Fn(ImdbQuery) -> Res<ImdbResults<'a, 'a>>) -> Res<serde_json::Value> {
+    if self.merge {
+      return to_json_merged(query(ImdbQuery::All)?);
+    }
+
+    let movies = if self.series {
+      None
+    } else {
+      Some(query(ImdbQuery::Movies)?)
+    };
+
+    let series = if self.movies {
+      None
+    } else {
+      Some(query(ImdbQuery::Series)?)
+    };
+
+    to_json(movies, series)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::params::parse_params;
+
+  #[derive(Debug, clap::Parser)]
+  struct Params {
+    #[clap(long)]
+    q: String,
+
+    #[clap(long)]
+    exact: bool,
+
+    #[clap(long)]
+    min_year: Option<u16>,
+  }
+
+  fn param(name: &str, value: Option<&str>) -> (String, Option<String>) {
+    (name.to_owned(), value.map(str::to_owned))
+  }
+
+  #[test]
+  fn test_parse_params() {
+    let params: Params =
+      parse_params([param("q", Some("alien")), param("exact", None), param("min_year", Some("1979"))])
+        .unwrap();
+    assert_eq!(params.q, "alien");
+    assert!(params.exact);
+    assert_eq!(params.min_year, Some(1979));
+
+    let params: Params = parse_params([param("q", Some("-sequel alien"))]).unwrap();
+    assert_eq!(params.q, "-sequel alien");
+    assert!(!params.exact);
+
+    let params: Params = parse_params([param("q", Some(""))]).unwrap();
+    assert_eq!(params.q, "");
+  }
+
+  #[test]
+  fn test_parse_params_errors() {
+    let e = parse_params::<Params>([param("q", None)]).unwrap_err();
+    assert!(e.to_string().contains("--q"), "{e}");
+
+    let e = parse_params::<Params>([param("q", Some("alien")), param("exact", Some("yes"))]).unwrap_err();
+    assert!(!e.to_string().starts_with("error:"), "{e}");
+
+    assert!(parse_params::<Params>([param("q", Some("alien")), param("min_year", Some("soon"))]).is_err());
+    assert!(parse_params::<Params>([param("q", Some("alien")), param("unknown", None)]).is_err());
+    assert!(parse_params::<Params>([]).is_err());
+  }
+}
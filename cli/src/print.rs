@@ -1,33 +1,357 @@
 #![warn(clippy::all)]
 
-use crate::search::SearchRes;
+use crate::columns::{truncate, Column, Columns};
 use humantime::format_duration;
 use prettytable::{color, format, Attr, Cell, Row, Table};
 use reqwest::Url;
-use serde::Serialize;
-use truncatable::Truncatable;
-use tvrank::imdb::{ImdbQuery, ImdbTitle};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use terminal_size::{terminal_size, Width};
+use tvrank::imdb::{ImdbQuery, ImdbRatingPrior, ImdbResults, ImdbTitle};
 use tvrank::utils::result::Res;
 
-#[derive(Debug, Clone, clap::ArgEnum)]
+#[derive(Debug, Clone, clap::ArgEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
   Json,
   Table,
   Yaml,
+  Csv,
+  Tsv,
+  Markdown,
+  Html,
+  Ndjson,
 }
 
 #[derive(Serialize)]
-struct OutputWrapper<'search_res, 'a, 'storage> {
-  movies: Option<&'search_res [&'a ImdbTitle<'storage>]>,
-  series: Option<&'search_res [&'a ImdbTitle<'storage>]>,
+struct TitleWrapper<'a> {
+  #[serde(flatten)]
+  title: &'a ImdbTitle<'a>,
+  weighted_rating: Option<f32>,
 }
 
-impl<'search_res, 'a, 'storage> OutputWrapper<'search_res, 'a, 'storage> {
-  fn new(
-    movies: Option<&'search_res [&'a ImdbTitle<'storage>]>,
-    series: Option<&'search_res [&'a ImdbTitle<'storage>]>,
-  ) -> Self {
-    Self { movies, series }
+impl<'a> TitleWrapper<'a> {
+  fn new(title: &'a ImdbTitle<'a>, prior: &ImdbRatingPrior) -> Self {
+    Self { title, weighted_rating: title.weighted_rating(prior).map(|rating| (rating * 10.0).round() / 10.0) }
+  }
+}
+
+fn wrap_titles<'a>(results: &'a mut ImdbResults) -> Vec<TitleWrapper<'a>> {
+  let prior = *results.order().prior();
+  results.page().iter().map(|&title| TitleWrapper::new(title, &prior)).collect()
+}
+
+#[derive(Serialize)]
+struct OutputWrapper<'a> {
+  movies: Option<Vec<TitleWrapper<'a>>>,
+  series: Option<Vec<TitleWrapper<'a>>>,
+}
+
+impl<'a> OutputWrapper<'a> {
+  fn new(movies: Option<&'a mut ImdbResults>, series: Option<&'a mut ImdbResults>) -> Self {
+    Self { movies: movies.map(wrap_titles), series: series.map(wrap_titles) }
+  }
+}
+
+#[derive(Serialize)]
+struct MergedOutputWrapper<'a> {
+  titles: Vec<TitleWrapper<'a>>,
+}
+
+impl<'a> MergedOutputWrapper<'a> {
+  fn new(titles: &'a mut ImdbResults) -> Self {
+    Self { titles: wrap_titles(titles) }
+  }
+}
+
+/// Convert movies and series results to JSON, as displayed by [`JsonPrinter`].
+pub fn to_json(mut movies: Option<ImdbResults>, mut series: Option<ImdbResults>) -> Res<serde_json::Value> {
+  Ok(serde_json::to_value(OutputWrapper::new(movies.as_mut(), series.as_mut()))?)
+}
+
+/// Convert merged results to JSON, as displayed by [`JsonPrinter`].
+pub fn to_json_merged(mut titles: ImdbResults) -> Res<serde_json::Value> {
+  Ok(serde_json::to_value(MergedOutputWrapper::new(&mut titles))?)
+}
+
+/// Every known field of a single title, as displayed by the info subcommand.
+#[derive(Serialize)]
+pub struct TitleDetails<'a> {
+  #[serde(flatten)]
+  title: TitleWrapper<'a>,
+  url: String,
+}
+
+impl<'a> TitleDetails<'a> {
+  /// The label of the rating field.
+  const RATING: &'static str = "Rating";
+
+  /// The label of the IMDB link field.
+  const LINK: &'static str = "IMDB Link";
+
+  pub fn new(title: &'a ImdbTitle<'a>, imdb_url: &Url, prior: &ImdbRatingPrior) -> Res<Self> {
+    Ok(Self {
+      title: TitleWrapper::new(title, prior),
+      url: imdb_url.join(&format!("{}", title.title_id()))?.to_string(),
+    })
+  }
+
+  /// The primary title, followed by the year it was released in, if known.
+  fn heading(&self) -> String {
+    let title = self.title.title;
+
+    match title.start_year() {
+      Some(year) => format!("{} ({year})", title.primary_title()),
+      None => title.primary_title().to_owned(),
+    }
+  }
+
+  fn rating(&self) -> Option<u8> {
+    self.title.title.rating().map(|rating| rating.rating())
+  }
+
+  /// The labelled fields of the title as text, in the order they are displayed.
+  fn fields(&self) -> Vec<(&'static str, String)> {
+    fn or_empty<T: ToString>(value: Option<T>) -> String {
+      value.map(|value| value.to_string()).unwrap_or_default()
+    }
+
+    let title = self.title.title;
+
+    vec![
+      ("IMDB ID", title.title_id().to_string()),
+      ("Primary Title", title.primary_title().to_owned()),
+      ("Original Title", or_empty(title.original_title())),
+      ("Type", title.title_type().to_string()),
+      ("Kind", title.kind().to_string()),
+      ("Year", or_empty(title.start_year())),
+      ("Runtime", or_empty(title.runtime().map(format_duration))),
+      ("Genres", title.genres().to_string()),
+      (Self::RATING, or_empty(self.rating().map(|rating| format!("{rating}/100")))),
+      ("Votes", or_empty(title.rating().map(|rating| rating.votes()))),
+      ("Weighted Rating", or_empty(self.title.weighted_rating.map(|rating| format!("{rating:.1}/100")))),
+      (Self::LINK, self.url.clone()),
+    ]
+  }
+}
+
+/// The results of a batch of search queries.
+pub struct BatchResults<'a> {
+  /// The queries that matched at least one title, along with their matches, in order.
+  pub matched: Vec<(String, ImdbResults<'a, 'a>)>,
+  /// The queries that did not match any title, in order.
+  pub unmatched: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct QueryTitleWrapper<'a> {
+  query: &'a str,
+  #[serde(flatten)]
+  title: TitleWrapper<'a>,
+}
+
+#[derive(Serialize)]
+struct BatchOutputWrapper<'a> {
+  titles: Vec<QueryTitleWrapper<'a>>,
+  unmatched: &'a [String],
+}
+
+impl<'a> BatchOutputWrapper<'a> {
+  fn new(batch: &'a mut BatchResults) -> Self {
+    let titles = batch
+      .matched
+      .iter_mut()
+      .flat_map(|(query, results)| {
+        let query = query.as_str();
+        wrap_titles(results)
+          .into_iter()
+          .map(move |title| QueryTitleWrapper { query, title })
+      })
+      .collect();
+
+    Self { titles, unmatched: &batch.unmatched }
+  }
+}
+
+/// The titles found in the sub-directories of a directory.
+pub struct ScanResults<'a> {
+  /// The titles found.
+  pub results: ImdbResults<'a, 'a>,
+  /// The sub-directory each title of `results` was found in.
+  pub dirs: HashMap<&'a ImdbTitle<'a>, PathBuf>,
+  /// The matches of sub-directories that did not match exactly one title, along with the
+  /// search terms used, when they are not included in `results`.
+  pub unmatched: Vec<(ImdbResults<'a, 'a>, String)>,
+  /// Whether any sub-directory was looked up.
+  pub at_least_one: bool,
+  /// Whether any sub-directory matched a title.
+  pub at_least_one_matched: bool,
+}
+
+impl<'a> ScanResults<'a> {
+  #[must_use]
+  pub fn new(results: ImdbResults<'a, 'a>) -> Self {
+    Self {
+      results,
+      dirs: HashMap::new(),
+      unmatched: Vec::new(),
+      at_least_one: false,
+      at_least_one_matched: false,
+    }
+  }
+
+  /// Add a title found in a sub-directory.
+  ///
+  /// # Arguments
+  ///
+  /// * `title` - The title found.
+  /// * `dir` - The sub-directory.
+  /// * `scanned` - Called with the title as soon as it is found, and returns whether it was
+  ///   already printed, in which case it is not kept.
+  pub fn push(
+    &mut self,
+    title: &'a ImdbTitle<'a>,
+    dir: &Path,
+    scanned: &mut dyn FnMut(&ImdbTitle, &Path) -> Res<bool>,
+  ) -> Res<()> {
+    self.at_least_one_matched = true;
+
+    if !scanned(title, dir)? {
+      self.results.push(title);
+      self.dirs.entry(title).or_insert_with(|| dir.to_path_buf());
+    }
+
+    Ok(())
+  }
+
+  /// Add the matches of a sub-directory.
+  ///
+  /// # Arguments
+  ///
+  /// * `matches` - The titles matching the sub-directory.
+  /// * `dir` - The sub-directory.
+  /// * `search_terms` - The search terms used to find the matches.
+  /// * `separate` - Whether to keep the matches separate if there is not exactly one.
+  /// * `scanned` - Called with each title kept with the others as soon as it is found, and
+  ///   returns whether it was already printed, in which case it is not kept.
+  pub fn add(
+    &mut self,
+    matches: ImdbResults<'a, 'a>,
+    dir: &Path,
+    search_terms: impl FnOnce() -> String,
+    separate: bool,
+    scanned: &mut dyn FnMut(&ImdbTitle, &Path) -> Res<bool>,
+  ) -> Res<()> {
+    if matches.len() != 1 {
+      if matches.len() > 1 {
+        self.at_least_one_matched = true;
+      }
+
+      if separate {
+        self.unmatched.push((matches, search_terms()));
+        return Ok(());
+      }
+    } else {
+      self.at_least_one_matched = true;
+    }
+
+    for title in matches {
+      if !scanned(title, dir)? {
+        self.results.push(title);
+        self.dirs.entry(title).or_insert_with(|| dir.to_path_buf());
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Describe how many titles were found, e.g. "Found 2 movie matches for `alien`".
+///
+/// # Arguments
+///
+/// * `results` - The titles found.
+/// * `query` - Whether the titles are movies, series or both.
+/// * `search_terms` - The search terms used to find the titles, if any.
+fn describe(results: &ImdbResults, query: ImdbQuery, search_terms: Option<&str>) -> String {
+  let search_terms = search_terms
+    .map(|search_terms| format!(" for `{search_terms}`"))
+    .unwrap_or_default();
+
+  if results.is_empty() {
+    return format!("No {query} matches found{search_terms}");
+  }
+
+  let num = results.total_len();
+  let matches = if num == 1 {
+    "match"
+  } else {
+    "matches"
+  };
+
+  if results.is_truncated() {
+    format!("Found {num} {query} {matches}{search_terms}, {} will be displayed", results.len())
+  } else {
+    format!("Found {num} {query} {matches}{search_terms}")
+  }
+}
+
+/// Describe how many titles were found by a batch of queries, e.g. "Found 5 matches for 3
+/// queries".
+///
+/// # Arguments
+///
+/// * `batch` - The results of the queries.
+fn describe_batch(batch: &BatchResults) -> String {
+  let num = batch.matched.iter().map(|(_, results)| results.total_len()).sum::<usize>();
+  let matches = if num == 1 {
+    "match"
+  } else {
+    "matches"
+  };
+  let queries = if batch.matched.len() == 1 {
+    "query"
+  } else {
+    "queries"
+  };
+
+  format!("Found {num} {matches} for {} {queries}", batch.matched.len())
+}
+
+/// The ratings (out of 100) at or above which titles are displayed in green, or in yellow
+/// rather than red.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RatingThresholds {
+  pub good: u8,
+  pub average: u8,
+}
+
+impl Default for RatingThresholds {
+  fn default() -> Self {
+    Self { good: 70, average: 60 }
+  }
+}
+
+/// The colour a rating is displayed in, depending on how good it is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RatingColor {
+  Green,
+  Yellow,
+  Red,
+}
+
+impl RatingColor {
+  fn of(rating: u8, thresholds: &RatingThresholds) -> Self {
+    match rating {
+      rating if rating >= thresholds.good => RatingColor::Green,
+      rating if rating >= thresholds.average => RatingColor::Yellow,
+      _ => RatingColor::Red,
+    }
   }
 }
 
@@ -36,11 +360,48 @@ pub trait Printer {
 
   fn print(
     &self,
-    movies: Option<SearchRes>,
-    series: Option<SearchRes>,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
     imdb_url: &Url,
     search_terms: Option<&str>,
   ) -> Res<()>;
+
+  fn print_merged(&self, titles: ImdbResults, imdb_url: &Url, search_terms: Option<&str>) -> Res<()>;
+
+  fn print_batch(&self, batch: BatchResults, imdb_url: &Url) -> Res<()>;
+
+  /// Print every known field of a single title.
+  fn print_info(&self, details: &TitleDetails) -> Res<()>;
+
+  /// Print a title as soon as it is found while scanning a directory, returning whether it was
+  /// printed. Titles that are not printed are printed along with the others by
+  /// [`Printer::print_scan`] once the scan is complete.
+  fn print_scanned(&self, _title: &ImdbTitle, _dir: &Path, _prior: &ImdbRatingPrior) -> Res<bool> {
+    Ok(false)
+  }
+
+  fn print_scan(&self, scan: ScanResults, imdb_url: &Url, query: ImdbQuery) -> Res<()> {
+    let print = |results, search_terms: Option<&str>| match query {
+      ImdbQuery::Series => self.print(None, Some(results), imdb_url, search_terms),
+      _ => self.print(Some(results), None, imdb_url, search_terms),
+    };
+
+    for (matches, search_terms) in scan.unmatched {
+      print(matches, Some(&search_terms))?;
+    }
+
+    if !scan.at_least_one {
+      eprintln!("No valid directory names");
+      return Ok(());
+    }
+
+    if !scan.at_least_one_matched {
+      eprintln!("None of the directories matched any titles");
+      return Ok(());
+    }
+
+    print(scan.results, None)
+  }
 }
 
 pub struct JsonPrinter;
@@ -59,18 +420,27 @@ impl Printer for JsonPrinter {
 
   fn print(
     &self,
-    mut movies: Option<SearchRes>,
-    mut series: Option<SearchRes>,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
     _imdb_url: &Url,
     _search_terms: Option<&str>,
   ) -> Res<()> {
-    println!(
-      "{}",
-      serde_json::to_string_pretty(&OutputWrapper::new(
-        movies.as_mut().map(|movies| movies.top_sorted_results()),
-        series.as_mut().map(|series| series.top_sorted_results()),
-      ))?
-    );
+    println!("{}", serde_json::to_string_pretty(&to_json(movies, series)?)?);
+    Ok(())
+  }
+
+  fn print_merged(&self, titles: ImdbResults, _imdb_url: &Url, _search_terms: Option<&str>) -> Res<()> {
+    println!("{}", serde_json::to_string_pretty(&to_json_merged(titles)?)?);
+    Ok(())
+  }
+
+  fn print_batch(&self, mut batch: BatchResults, _imdb_url: &Url) -> Res<()> {
+    println!("{}", serde_json::to_string_pretty(&BatchOutputWrapper::new(&mut batch))?);
+    Ok(())
+  }
+
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    println!("{}", serde_json::to_string_pretty(details)?);
     Ok(())
   }
 }
@@ -91,198 +461,1194 @@ impl Printer for YamlPrinter {
 
   fn print(
     &self,
-    mut movies: Option<SearchRes>,
-    mut series: Option<SearchRes>,
+    mut movies: Option<ImdbResults>,
+    mut series: Option<ImdbResults>,
     _imdb_url: &Url,
     _search_terms: Option<&str>,
   ) -> Res<()> {
-    println!(
-      "{}",
-      serde_yaml::to_string(&OutputWrapper::new(
-        movies.as_mut().map(|movies| movies.top_sorted_results()),
-        series.as_mut().map(|series| series.top_sorted_results()),
-      ))?
-    );
+    println!("{}", serde_yaml::to_string(&OutputWrapper::new(movies.as_mut(), series.as_mut()))?);
+    Ok(())
+  }
+
+  fn print_merged(&self, mut titles: ImdbResults, _imdb_url: &Url, _search_terms: Option<&str>) -> Res<()> {
+    println!("{}", serde_yaml::to_string(&MergedOutputWrapper::new(&mut titles))?);
+    Ok(())
+  }
+
+  fn print_batch(&self, mut batch: BatchResults, _imdb_url: &Url) -> Res<()> {
+    println!("{}", serde_yaml::to_string(&BatchOutputWrapper::new(&mut batch))?);
+    Ok(())
+  }
+
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    println!("{}", serde_yaml::to_string(details)?);
     Ok(())
   }
 }
 
-#[derive(Clone)]
-pub struct TablePrinter {
-  color: bool,
+#[derive(Serialize)]
+struct ScannedTitleWrapper<'a> {
+  #[serde(flatten)]
+  title: TitleWrapper<'a>,
+  dir: Cow<'a, str>,
 }
 
-impl Printer for TablePrinter {
+/// Prints each title as a JSON object on its own line (NDJSON), as soon as it is available.
+pub struct NdjsonPrinter;
+
+impl NdjsonPrinter {
+  #[must_use]
+  pub fn new() -> Self {
+    Self
+  }
+
+  fn print_line(value: &impl Serialize) -> Res<()> {
+    let mut stdout = io::stdout().lock();
+    serde_json::to_writer(&mut stdout, value)?;
+    writeln!(stdout)?;
+    Ok(())
+  }
+}
+
+impl Printer for NdjsonPrinter {
   fn get_format(&self) -> OutputFormat {
-    OutputFormat::Table
+    OutputFormat::Ndjson
   }
 
   fn print(
     &self,
-    movies: Option<SearchRes>,
-    series: Option<SearchRes>,
-    imdb_url: &Url,
-    search_terms: Option<&str>,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
+    _imdb_url: &Url,
+    _search_terms: Option<&str>,
   ) -> Res<()> {
-    if let Some(movies) = movies {
-      self.print_results(movies, imdb_url, ImdbQuery::Movies, search_terms)?;
+    for mut results in movies.into_iter().chain(series) {
+      for title in wrap_titles(&mut results) {
+        Self::print_line(&title)?;
+      }
     }
-    if let Some(series) = series {
-      self.print_results(series, imdb_url, ImdbQuery::Series, search_terms)?;
+
+    Ok(())
+  }
+
+  fn print_merged(&self, titles: ImdbResults, imdb_url: &Url, search_terms: Option<&str>) -> Res<()> {
+    self.print(Some(titles), None, imdb_url, search_terms)
+  }
+
+  fn print_batch(&self, mut batch: BatchResults, _imdb_url: &Url) -> Res<()> {
+    for title in BatchOutputWrapper::new(&mut batch).titles {
+      Self::print_line(&title)?;
+    }
+
+    if !batch.unmatched.is_empty() {
+      eprintln!("No matches found for:");
+
+      for query in &batch.unmatched {
+        eprintln!("  {query}");
+      }
+    }
+
+    Ok(())
+  }
+
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    Self::print_line(details)
+  }
+
+  fn print_scanned(&self, title: &ImdbTitle, dir: &Path, prior: &ImdbRatingPrior) -> Res<bool> {
+    Self::print_line(&ScannedTitleWrapper {
+      title: TitleWrapper::new(title, prior),
+      dir: dir.to_string_lossy(),
+    })?;
+    Ok(true)
+  }
+
+  /// Print the titles that could not be printed as soon as they were found, e.g. because they
+  /// are sorted, along with the directory each was found in.
+  fn print_scan(&self, mut scan: ScanResults, _imdb_url: &Url, _query: ImdbQuery) -> Res<()> {
+    if !scan.at_least_one {
+      eprintln!("No valid directory names");
+      return Ok(());
+    }
+
+    if !scan.at_least_one_matched {
+      eprintln!("None of the directories matched any titles");
+      return Ok(());
+    }
+
+    let prior = *scan.results.order().prior();
+
+    for &title in scan.results.page() {
+      let dir = scan.dirs.get(title).map(|dir| dir.to_string_lossy()).unwrap_or_default();
+      Self::print_line(&ScannedTitleWrapper { title: TitleWrapper::new(title, &prior), dir })?;
     }
 
     Ok(())
   }
 }
 
-impl TablePrinter {
+/// Prints titles as delimiter-separated values (e.g. CSV or TSV) with a header row, for use
+/// in spreadsheets.
+pub struct CsvPrinter {
+  delimiter: u8,
+}
+
+impl CsvPrinter {
+  const HEADER: [&'static str; 10] = [
+    "Primary Title",
+    "Original Title",
+    "Year",
+    "Rating",
+    "Votes",
+    "Runtime (minutes)",
+    "Genres",
+    "Type",
+    "IMDB ID",
+    "IMDB Link",
+  ];
+
   #[must_use]
-  pub fn new(color: bool) -> Self {
-    Self { color }
+  pub fn new(delimiter: u8) -> Self {
+    Self { delimiter }
   }
 
-  fn print_results(
+  fn writer<W: io::Write>(&self, out: W) -> csv::Writer<W> {
+    csv::WriterBuilder::new().delimiter(self.delimiter).from_writer(out)
+  }
+
+  fn write_results<W: io::Write>(
     &self,
-    mut results: SearchRes,
+    out: W,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
     imdb_url: &Url,
-    query: ImdbQuery,
-    search_terms: Option<&str>,
   ) -> Res<()> {
-    if results.is_empty() {
-      if let Some(search_terms) = search_terms {
-        eprintln!("No {} matches found for `{search_terms}`", query);
-      } else {
-        eprintln!("No {} matches found", query);
-      }
-    } else {
-      let num = results.total_len();
-      let matches = if num == 1 {
-        "match"
-      } else {
-        "matches"
-      };
-
-      if let Some(search_terms) = search_terms {
-        if results.is_truncated() {
-          println!(
-            "Found {num} {} {matches} for `{search_terms}`, {} will be displayed:",
-            query,
-            results.len()
-          )
-        } else {
-          println!("Found {num} {} {matches} for `{search_terms}`:", query)
-        }
-      } else if results.is_truncated() {
-        println!("Found {num} {} {matches}, {} will be displayed:", query, results.len());
-      } else {
-        println!("Found {num} {} {matches}:", query);
-      }
-
-      let mut table = create_table(self.color);
+    let mut writer = self.writer(out);
+    writer.write_record(Self::HEADER)?;
 
-      for res in results.top_sorted_results() {
-        let row = self.create_table_row(res, imdb_url)?;
-        table.add_row(row);
+    for mut results in movies.into_iter().chain(series) {
+      for title in results.page() {
+        writer.write_record(Self::record(title, imdb_url)?)?;
       }
-      table.printstd();
-      println!();
     }
 
+    writer.flush()?;
     Ok(())
   }
 
-  fn create_table_row(&self, title: &ImdbTitle, imdb_url: &Url) -> Res<Row> {
-    static GREEN: Attr = Attr::ForegroundColor(color::GREEN);
-    static YELLOW: Attr = Attr::ForegroundColor(color::YELLOW);
-    static RED: Attr = Attr::ForegroundColor(color::RED);
+  fn record(title: &ImdbTitle, imdb_url: &Url) -> Res<Vec<String>> {
+    fn or_empty<T: ToString>(value: Option<T>) -> String {
+      value.map(|value| value.to_string()).unwrap_or_default()
+    }
 
-    let mut row = Row::new(vec![]);
+    let title_id = title.title_id();
 
-    row.add_cell(Cell::new(&Truncatable::from(title.primary_title()).truncate(50)));
+    Ok(vec![
+      title.primary_title().to_owned(),
+      or_empty(title.original_title()),
+      or_empty(title.start_year()),
+      or_empty(title.rating().map(|rating| rating.rating())),
+      or_empty(title.rating().map(|rating| rating.votes())),
+      or_empty(title.runtime().map(|runtime| runtime.as_secs() / 60)),
+      title.genres().to_string(),
+      title.title_type().to_string(),
+      title_id.to_string(),
+      imdb_url.join(&format!("{}", title_id))?.to_string(),
+    ])
+  }
+}
 
-    if let Some(original_title) = title.original_title() {
-      row.add_cell(Cell::new(&Truncatable::from(original_title).truncate(30)));
+impl Printer for CsvPrinter {
+  fn get_format(&self) -> OutputFormat {
+    if self.delimiter == b'\t' {
+      OutputFormat::Tsv
     } else {
-      row.add_cell(Cell::new(""));
+      OutputFormat::Csv
     }
+  }
 
-    if let Some(year) = title.start_year() {
-      row.add_cell(Cell::new(&format!("{}", year)));
-    } else {
-      row.add_cell(Cell::new(""));
-    }
+  fn print(
+    &self,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
+    imdb_url: &Url,
+    _search_terms: Option<&str>,
+  ) -> Res<()> {
+    self.write_results(io::stdout(), movies, series, imdb_url)
+  }
 
-    if let Some(rating) = title.rating() {
-      let rating_text = &format!("{}/100", rating.rating());
+  fn print_merged(&self, titles: ImdbResults, imdb_url: &Url, search_terms: Option<&str>) -> Res<()> {
+    self.print(Some(titles), None, imdb_url, search_terms)
+  }
 
-      let mut rating_cell = Cell::new(rating_text);
-      if self.color {
-        rating_cell = rating_cell.with_style(match rating {
-          rating if rating.rating() >= 70 => GREEN,
-          rating if (60..70).contains(&rating.rating()) => YELLOW,
-          _ => RED,
-        });
-      }
+  fn print_batch(&self, batch: BatchResults, imdb_url: &Url) -> Res<()> {
+    let mut writer = self.writer(io::stdout());
+    writer.write_record(["Query"].into_iter().chain(Self::HEADER))?;
 
-      row.add_cell(rating_cell);
-      row.add_cell(Cell::new(&format!("{}", rating.votes())));
-    } else {
-      row.add_cell(Cell::new(""));
-      row.add_cell(Cell::new(""));
+    for (query, mut results) in batch.matched {
+      for title in results.page() {
+        let mut record = Self::record(title, imdb_url)?;
+        record.insert(0, query.clone());
+        writer.write_record(record)?;
+      }
     }
 
-    if let Some(runtime) = title.runtime() {
-      row.add_cell(Cell::new(&format_duration(runtime).to_string()));
-    } else {
-      row.add_cell(Cell::new(""));
-    }
+    writer.flush()?;
 
-    row.add_cell(Cell::new(&format!("{}", title.genres())));
-    row.add_cell(Cell::new(&format!("{}", title.title_type())));
+    if !batch.unmatched.is_empty() {
+      eprintln!("No matches found for:");
 
-    let title_id = title.title_id();
-    row.add_cell(Cell::new(&format!("{}", title_id)));
+      for query in &batch.unmatched {
+        eprintln!("  {query}");
+      }
+    }
 
-    let url = imdb_url.join(&format!("{}", title_id))?;
-    row.add_cell(Cell::new(url.as_str()));
+    Ok(())
+  }
 
-    Ok(row)
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    let fields = details.fields();
+    let mut writer = self.writer(io::stdout());
+    writer.write_record(fields.iter().map(|(name, _)| name))?;
+    writer.write_record(fields.iter().map(|(_, value)| value))?;
+    writer.flush()?;
+    Ok(())
   }
 }
 
-fn create_table(color: bool) -> Table {
-  let mut table = Table::new();
-
-  let table_format = format::FormatBuilder::new()
-    .column_separator('│')
-    .borders('│')
-    .padding(1, 1)
-    .build();
-
-  table.set_format(table_format);
+#[derive(Clone)]
+pub struct TablePrinter {
+  color: bool,
+  columns: Columns,
+  ratings: RatingThresholds,
+}
 
-  #[macro_export]
-  macro_rules! make_bold {
-    ($title: expr, $color: expr) => {
-      match $color {
-        true => Cell::new($title).with_style(Attr::Bold),
-        false => Cell::new($title),
-      }
-    };
+impl Printer for TablePrinter {
+  fn get_format(&self) -> OutputFormat {
+    OutputFormat::Table
   }
 
-  table.add_row(Row::new(vec![
-    make_bold!("Primary Title", color),
-    make_bold!("Original Title", color),
-    make_bold!("Year", color),
-    make_bold!("Rating", color),
-    make_bold!("Votes", color),
-    make_bold!("Runtime", color),
-    make_bold!("Genres", color),
-    make_bold!("Type", color),
-    make_bold!("IMDB ID", color),
-    make_bold!("IMDB Link", color),
-  ]));
-
-  table
+  fn print(
+    &self,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
+    imdb_url: &Url,
+    search_terms: Option<&str>,
+  ) -> Res<()> {
+    if let Some(movies) = movies {
+      self.print_results(movies, imdb_url, ImdbQuery::Movies, search_terms)?;
+    }
+    if let Some(series) = series {
+      self.print_results(series, imdb_url, ImdbQuery::Series, search_terms)?;
+    }
+
+    Ok(())
+  }
+
+  fn print_merged(&self, titles: ImdbResults, imdb_url: &Url, search_terms: Option<&str>) -> Res<()> {
+    self.print_results(titles, imdb_url, ImdbQuery::All, search_terms)
+  }
+
+  fn print_batch(&self, batch: BatchResults, imdb_url: &Url) -> Res<()> {
+    if !batch.matched.is_empty() {
+      println!("{}:", describe_batch(&batch));
+
+      let columns = self.columns.with_query();
+      let mut rows = Vec::new();
+
+      for (query, mut results) in batch.matched {
+        for res in results.page() {
+          rows.push((
+            self.create_table_row(&columns, res, imdb_url, Some(&query))?,
+            res.rating().map(|r| r.rating()),
+          ));
+        }
+      }
+
+      self.print_table(&columns, rows);
+    }
+
+    if !batch.unmatched.is_empty() {
+      eprintln!("No matches found for:");
+
+      for query in &batch.unmatched {
+        eprintln!("  {query}");
+      }
+    }
+
+    Ok(())
+  }
+
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    println!("{}:", details.heading());
+
+    let mut table = Table::new();
+    table.set_format(table_format());
+
+    for (name, value) in details.fields() {
+      let mut name_cell = Cell::new(name);
+      let mut value_cell = Cell::new(&value);
+
+      if self.color {
+        name_cell = name_cell.with_style(Attr::Bold);
+
+        if let (TitleDetails::RATING, Some(rating)) = (name, details.rating()) {
+          value_cell = value_cell.with_style(self.rating_style(rating));
+        }
+      }
+
+      table.add_row(Row::new(vec![name_cell, value_cell]));
+    }
+
+    table.printstd();
+    println!();
+    Ok(())
+  }
+}
+
+impl TablePrinter {
+  #[must_use]
+  pub fn new(color: bool, columns: Columns, ratings: RatingThresholds) -> Self {
+    Self { color, columns, ratings }
+  }
+
+  fn rating_style(&self, rating: u8) -> Attr {
+    match RatingColor::of(rating, &self.ratings) {
+      RatingColor::Green => Attr::ForegroundColor(color::GREEN),
+      RatingColor::Yellow => Attr::ForegroundColor(color::YELLOW),
+      RatingColor::Red => Attr::ForegroundColor(color::RED),
+    }
+  }
+
+  fn print_results(
+    &self,
+    mut results: ImdbResults,
+    imdb_url: &Url,
+    query: ImdbQuery,
+    search_terms: Option<&str>,
+  ) -> Res<()> {
+    if results.is_empty() {
+      eprintln!("{}", describe(&results, query, search_terms));
+    } else {
+      println!("{}:", describe(&results, query, search_terms));
+
+      let mut rows = Vec::new();
+
+      for res in results.page() {
+        rows.push((
+          self.create_table_row(&self.columns, res, imdb_url, None)?,
+          res.rating().map(|r| r.rating()),
+        ));
+      }
+
+      self.print_table(&self.columns, rows);
+    }
+
+    Ok(())
+  }
+
+  /// The text of each column for a title.
+  ///
+  /// # Arguments
+  ///
+  /// * `columns` - The columns to display.
+  /// * `title` - The title to display.
+  /// * `imdb_url` - The URL IMDB links are relative to.
+  /// * `query` - The query that matched the title, if any.
+  fn create_table_row(
+    &self,
+    columns: &Columns,
+    title: &ImdbTitle,
+    imdb_url: &Url,
+    query: Option<&str>,
+  ) -> Res<Vec<String>> {
+    columns
+      .iter()
+      .map(|spec| match spec.column {
+        Column::Query => Ok(query.unwrap_or_default().to_owned()),
+        column => column.text(title, imdb_url),
+      })
+      .collect()
+  }
+
+  /// Print rows of titles as a table that fits in the terminal, if possible.
+  ///
+  /// # Arguments
+  ///
+  /// * `columns` - The columns to display.
+  /// * `rows` - The text of each row along with the rating of its title, if any.
+  fn print_table(&self, columns: &Columns, rows: Vec<(Vec<String>, Option<u8>)>) {
+    let (texts, ratings): (Vec<_>, Vec<_>) = rows.into_iter().unzip();
+    let max_width = terminal_size().map(|(Width(width), _)| usize::from(width));
+    let widths = columns.layout(&texts, max_width);
+
+    let mut table = create_table(self.color, columns, &widths);
+
+    for (text, rating) in texts.iter().zip(ratings) {
+      let cells = columns
+        .iter()
+        .zip(text)
+        .zip(&widths)
+        .map(|((spec, text), &width)| {
+          let cell = Cell::new(&truncate(text, width));
+
+          match rating {
+            Some(rating) if self.color && spec.column == Column::Rating => {
+              cell.with_style(self.rating_style(rating))
+            }
+            _ => cell,
+          }
+        })
+        .collect();
+
+      table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    println!();
+  }
+}
+
+/// The columns of a title in Markdown and HTML reports.
+const REPORT_HEADER: [&str; 9] =
+  ["Title", "Original Title", "Year", "Rating", "Votes", "Runtime", "Genres", "Type", "IMDB ID"];
+
+/// The columns of a title in Markdown and HTML reports, as unescaped text.
+struct ReportRow {
+  cells: [String; 9],
+  rating: Option<u8>,
+  url: Url,
+}
+
+impl ReportRow {
+  /// The position of the rating in the columns.
+  const RATING: usize = 3;
+
+  fn new(title: &ImdbTitle, imdb_url: &Url) -> Res<Self> {
+    fn or_empty<T: ToString>(value: Option<T>) -> String {
+      value.map(|value| value.to_string()).unwrap_or_default()
+    }
+
+    let title_id = title.title_id();
+    let rating = title.rating();
+
+    Ok(Self {
+      cells: [
+        title.primary_title().to_owned(),
+        or_empty(title.original_title()),
+        or_empty(title.start_year()),
+        or_empty(rating.map(|rating| format!("{}/100", rating.rating()))),
+        or_empty(rating.map(|rating| rating.votes())),
+        or_empty(title.runtime().map(|runtime| format_duration(runtime).to_string())),
+        title.genres().to_string(),
+        title.title_type().to_string(),
+        title_id.to_string(),
+      ],
+      rating: rating.map(|rating| rating.rating()),
+      url: imdb_url.join(&format!("{}", title_id))?,
+    })
+  }
+}
+
+/// Escape text so that it is displayed as-is in a Markdown table, replacing line breaks
+/// with spaces since they would end the row.
+///
+/// # Arguments
+///
+/// * `text` - The text to escape.
+fn escape_markdown(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '\r' => {}
+      '\n' => escaped.push(' '),
+      '\\' | '|' | '[' | ']' | '*' | '_' | '`' | '<' | '>' => {
+        escaped.push('\\');
+        escaped.push(c);
+      }
+      c => escaped.push(c),
+    }
+  }
+
+  escaped
+}
+
+/// Escape text so that it is displayed as-is in an HTML document.
+///
+/// # Arguments
+///
+/// * `text` - The text to escape.
+fn escape_html(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&#39;"),
+      c => escaped.push(c),
+    }
+  }
+
+  escaped
+}
+
+/// Prints titles as Markdown tables, e.g. to share them in wikis and chats.
+pub struct MarkdownPrinter {
+  ratings: RatingThresholds,
+}
+
+impl MarkdownPrinter {
+  #[must_use]
+  pub fn new(ratings: RatingThresholds) -> Self {
+    Self { ratings }
+  }
+
+  fn rating_marker(&self, rating: u8) -> &'static str {
+    match RatingColor::of(rating, &self.ratings) {
+      RatingColor::Green => "🟢",
+      RatingColor::Yellow => "🟡",
+      RatingColor::Red => "🔴",
+    }
+  }
+
+  /// Write a table of titles, each along with the query that found it, if any.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - Where to write the table.
+  /// * `titles` - The titles, each along with the query that found it, if any.
+  /// * `imdb_url` - The base URL of IMDB titles.
+  fn write_table(&self, out: &mut String, titles: &[(Option<&str>, &ImdbTitle)], imdb_url: &Url) -> Res<()> {
+    let with_query = titles.iter().any(|(query, _)| query.is_some());
+    let header = with_query.then_some("Query").into_iter().chain(REPORT_HEADER);
+
+    writeln!(out, "| {} |", header.clone().collect::<Vec<_>>().join(" | "))?;
+    writeln!(out, "|{}", header.map(|_| " --- |").collect::<String>())?;
+
+    for (query, title) in titles {
+      let row = ReportRow::new(title, imdb_url)?;
+      let mut cells: Vec<String> = row.cells.iter().map(|cell| escape_markdown(cell)).collect();
+
+      cells[0] = format!("[{}]({})", cells[0], row.url);
+
+      if let Some(rating) = row.rating {
+        cells[ReportRow::RATING] = format!("{} {}", self.rating_marker(rating), cells[ReportRow::RATING]);
+      }
+
+      if with_query {
+        cells.insert(0, escape_markdown(query.unwrap_or_default()));
+      }
+
+      writeln!(out, "| {} |", cells.join(" | "))?;
+    }
+
+    Ok(())
+  }
+
+  fn write_results(
+    &self,
+    out: &mut String,
+    mut results: ImdbResults,
+    imdb_url: &Url,
+    query: ImdbQuery,
+    search_terms: Option<&str>,
+  ) -> Res<()> {
+    if results.is_empty() {
+      writeln!(out, "{}", describe(&results, query, search_terms))?;
+    } else {
+      writeln!(out, "{}:", describe(&results, query, search_terms))?;
+      writeln!(out)?;
+      let titles: Vec<_> = results.page().iter().map(|&title| (None, title)).collect();
+      self.write_table(out, &titles, imdb_url)?;
+    }
+
+    writeln!(out)?;
+    Ok(())
+  }
+}
+
+impl Printer for MarkdownPrinter {
+  fn get_format(&self) -> OutputFormat {
+    OutputFormat::Markdown
+  }
+
+  fn print(
+    &self,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
+    imdb_url: &Url,
+    search_terms: Option<&str>,
+  ) -> Res<()> {
+    let mut out = String::new();
+
+    if let Some(movies) = movies {
+      self.write_results(&mut out, movies, imdb_url, ImdbQuery::Movies, search_terms)?;
+    }
+    if let Some(series) = series {
+      self.write_results(&mut out, series, imdb_url, ImdbQuery::Series, search_terms)?;
+    }
+
+    print!("{out}");
+    Ok(())
+  }
+
+  fn print_merged(&self, titles: ImdbResults, imdb_url: &Url, search_terms: Option<&str>) -> Res<()> {
+    let mut out = String::new();
+    self.write_results(&mut out, titles, imdb_url, ImdbQuery::All, search_terms)?;
+    print!("{out}");
+    Ok(())
+  }
+
+  fn print_batch(&self, mut batch: BatchResults, imdb_url: &Url) -> Res<()> {
+    let mut out = String::new();
+
+    if !batch.matched.is_empty() {
+      writeln!(out, "{}:", describe_batch(&batch))?;
+      writeln!(out)?;
+
+      let titles: Vec<_> = batch
+        .matched
+        .iter_mut()
+        .flat_map(|(query, results)| {
+          let query = query.as_str();
+          results.page().iter().map(move |&title| (Some(query), title))
+        })
+        .collect();
+
+      self.write_table(&mut out, &titles, imdb_url)?;
+      writeln!(out)?;
+    }
+
+    if !batch.unmatched.is_empty() {
+      writeln!(out, "No matches found for:")?;
+      writeln!(out)?;
+
+      for query in &batch.unmatched {
+        writeln!(out, "- {}", escape_markdown(query))?;
+      }
+
+      writeln!(out)?;
+    }
+
+    print!("{out}");
+    Ok(())
+  }
+
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    let mut out = String::new();
+    writeln!(out, "[{}]({}):", escape_markdown(&details.heading()), details.url)?;
+    writeln!(out)?;
+    writeln!(out, "| Field | Value |")?;
+    writeln!(out, "| --- | --- |")?;
+
+    for (name, value) in details.fields() {
+      let value = match (name, details.rating()) {
+        (TitleDetails::RATING, Some(rating)) => {
+          format!("{} {}", self.rating_marker(rating), escape_markdown(&value))
+        }
+        (TitleDetails::LINK, _) => format!("<{value}>"),
+        _ => escape_markdown(&value),
+      };
+
+      writeln!(out, "| {name} | {value} |")?;
+    }
+
+    print!("{out}");
+    Ok(())
+  }
+}
+
+/// Prints titles as standalone HTML documents, e.g. to share them as reports.
+pub struct HtmlPrinter {
+  ratings: RatingThresholds,
+}
+
+impl HtmlPrinter {
+  const STYLE: &'static str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+th { background: #eee; }
+.green { color: #080; }
+.yellow { color: #a70; }
+.red { color: #c00; }";
+
+  #[must_use]
+  pub fn new(ratings: RatingThresholds) -> Self {
+    Self { ratings }
+  }
+
+  /// Print a standalone HTML document.
+  ///
+  /// # Arguments
+  ///
+  /// * `title` - The title of the document, as unescaped text.
+  /// * `body` - The body of the document, as HTML.
+  fn print_document(title: &str, body: &str) {
+    println!("<!DOCTYPE html>");
+    println!("<html lang=\"en\">");
+    println!("<head>");
+    println!("<meta charset=\"utf-8\">");
+    println!("<title>{}</title>", escape_html(title));
+    println!("<style>\n{}\n</style>", Self::STYLE);
+    println!("</head>");
+    println!("<body>");
+    println!("<h1>{}</h1>", escape_html(title));
+    print!("{body}");
+    println!("</body>");
+    println!("</html>");
+  }
+
+  fn color_class(&self, rating: u8) -> &'static str {
+    match RatingColor::of(rating, &self.ratings) {
+      RatingColor::Green => "green",
+      RatingColor::Yellow => "yellow",
+      RatingColor::Red => "red",
+    }
+  }
+
+  /// Write a table of titles, each along with the query that found it, if any.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - Where to write the table.
+  /// * `titles` - The titles, each along with the query that found it, if any.
+  /// * `imdb_url` - The base URL of IMDB titles.
+  fn write_table(&self, out: &mut String, titles: &[(Option<&str>, &ImdbTitle)], imdb_url: &Url) -> Res<()> {
+    let with_query = titles.iter().any(|(query, _)| query.is_some());
+
+    writeln!(out, "<table>")?;
+    write!(out, "<tr>")?;
+
+    for name in with_query.then_some("Query").into_iter().chain(REPORT_HEADER) {
+      write!(out, "<th>{name}</th>")?;
+    }
+
+    writeln!(out, "</tr>")?;
+
+    for (query, title) in titles {
+      let row = ReportRow::new(title, imdb_url)?;
+      write!(out, "<tr>")?;
+
+      if with_query {
+        write!(out, "<td>{}</td>", escape_html(query.unwrap_or_default()))?;
+      }
+
+      for (i, cell) in row.cells.iter().enumerate() {
+        let cell = escape_html(cell);
+
+        match (i, row.rating) {
+          (0, _) => write!(out, "<td><a href=\"{}\">{cell}</a></td>", escape_html(row.url.as_str()))?,
+          (ReportRow::RATING, Some(rating)) => {
+            write!(out, "<td class=\"{}\">{cell}</td>", self.color_class(rating))?
+          }
+          _ => write!(out, "<td>{cell}</td>")?,
+        }
+      }
+
+      writeln!(out, "</tr>")?;
+    }
+
+    writeln!(out, "</table>")?;
+    Ok(())
+  }
+
+  fn write_results(
+    &self,
+    out: &mut String,
+    mut results: ImdbResults,
+    imdb_url: &Url,
+    query: ImdbQuery,
+    search_terms: Option<&str>,
+  ) -> Res<()> {
+    let heading = match query {
+      ImdbQuery::Movies => "Movies",
+      ImdbQuery::Series => "Series",
+      ImdbQuery::All => "Titles",
+    };
+
+    writeln!(out, "<h2>{heading}</h2>")?;
+    writeln!(out, "<p>{}</p>", escape_html(&describe(&results, query, search_terms)))?;
+
+    if !results.is_empty() {
+      let titles: Vec<_> = results.page().iter().map(|&title| (None, title)).collect();
+      self.write_table(out, &titles, imdb_url)?;
+    }
+
+    Ok(())
+  }
+
+  /// Write the summary of a directory scan, with the number of titles found and how they
+  /// are rated.
+  ///
+  /// # Arguments
+  ///
+  /// * `out` - Where to write the summary.
+  /// * `scan` - The results of the scan.
+  /// * `n_ambiguous` - The number of directories that matched several titles.
+  /// * `n_not_found` - The number of directories that did not match any title.
+  fn write_scan_summary(
+    &self,
+    out: &mut String,
+    scan: &ScanResults,
+    n_ambiguous: usize,
+    n_not_found: usize,
+  ) -> Res<()> {
+    // The statistics cover all the titles found, not only the displayed ones.
+    let total = scan.results.total_len();
+    let displayed = scan.results.len();
+    let found = scan.results.iter().count();
+    let ratings: Vec<u8> = scan
+      .results
+      .iter()
+      .filter_map(|title| title.rating())
+      .map(|r| r.rating())
+      .collect();
+    let count = |color| {
+      ratings
+        .iter()
+        .filter(|&&rating| RatingColor::of(rating, &self.ratings) == color)
+        .count()
+    };
+
+    writeln!(out, "<h2>Summary</h2>")?;
+    writeln!(out, "<ul>")?;
+    writeln!(out, "<li>Titles found: {total}</li>")?;
+
+    if total > displayed {
+      writeln!(out, "<li>Titles displayed: {displayed}</li>")?;
+    }
+
+    if !ratings.is_empty() {
+      let average = ratings.iter().map(|&rating| f32::from(rating)).sum::<f32>() / ratings.len() as f32;
+      writeln!(out, "<li>Average rating: {average:.1}/100</li>")?;
+      let RatingThresholds { good, average } = self.ratings;
+      writeln!(out, "<li class=\"green\">Rated {good} or more: {}</li>", count(RatingColor::Green))?;
+      writeln!(
+        out,
+        "<li class=\"yellow\">Rated {average} to {}: {}</li>",
+        good.saturating_sub(1),
+        count(RatingColor::Yellow)
+      )?;
+      writeln!(out, "<li class=\"red\">Rated below {average}: {}</li>", count(RatingColor::Red))?;
+    }
+
+    if ratings.len() < found {
+      writeln!(out, "<li>Not rated: {}</li>", found - ratings.len())?;
+    }
+
+    if n_ambiguous > 0 {
+      writeln!(out, "<li>Directories matching several titles: {n_ambiguous}</li>")?;
+    }
+
+    if n_not_found > 0 {
+      writeln!(out, "<li>Directories not matching any title: {n_not_found}</li>")?;
+    }
+
+    writeln!(out, "</ul>")?;
+    Ok(())
+  }
+}
+
+impl Printer for HtmlPrinter {
+  fn get_format(&self) -> OutputFormat {
+    OutputFormat::Html
+  }
+
+  fn print(
+    &self,
+    movies: Option<ImdbResults>,
+    series: Option<ImdbResults>,
+    imdb_url: &Url,
+    search_terms: Option<&str>,
+  ) -> Res<()> {
+    let mut body = String::new();
+
+    if let Some(movies) = movies {
+      self.write_results(&mut body, movies, imdb_url, ImdbQuery::Movies, search_terms)?;
+    }
+    if let Some(series) = series {
+      self.write_results(&mut body, series, imdb_url, ImdbQuery::Series, search_terms)?;
+    }
+
+    Self::print_document("TVrank", &body);
+    Ok(())
+  }
+
+  fn print_merged(&self, titles: ImdbResults, imdb_url: &Url, search_terms: Option<&str>) -> Res<()> {
+    let mut body = String::new();
+    self.write_results(&mut body, titles, imdb_url, ImdbQuery::All, search_terms)?;
+    Self::print_document("TVrank", &body);
+    Ok(())
+  }
+
+  fn print_batch(&self, mut batch: BatchResults, imdb_url: &Url) -> Res<()> {
+    let mut body = String::new();
+
+    writeln!(body, "<h2>Titles</h2>")?;
+    writeln!(body, "<p>{}</p>", escape_html(&describe_batch(&batch)))?;
+
+    let titles: Vec<_> = batch
+      .matched
+      .iter_mut()
+      .flat_map(|(query, results)| {
+        let query = query.as_str();
+        results.page().iter().map(move |&title| (Some(query), title))
+      })
+      .collect();
+
+    if !titles.is_empty() {
+      self.write_table(&mut body, &titles, imdb_url)?;
+    }
+
+    if !batch.unmatched.is_empty() {
+      writeln!(body, "<h2>No Matches</h2>")?;
+      writeln!(body, "<ul>")?;
+
+      for query in &batch.unmatched {
+        writeln!(body, "<li>{}</li>", escape_html(query))?;
+      }
+
+      writeln!(body, "</ul>")?;
+    }
+
+    Self::print_document("TVrank", &body);
+    Ok(())
+  }
+
+  fn print_info(&self, details: &TitleDetails) -> Res<()> {
+    let mut body = String::new();
+    writeln!(body, "<table>")?;
+
+    for (name, value) in details.fields() {
+      let value = escape_html(&value);
+      write!(body, "<tr><th>{name}</th>")?;
+
+      match (name, details.rating()) {
+        (TitleDetails::RATING, Some(rating)) => {
+          write!(body, "<td class=\"{}\">{value}</td>", self.color_class(rating))?
+        }
+        (TitleDetails::LINK, _) => write!(body, "<td><a href=\"{value}\">{value}</a></td>")?,
+        _ => write!(body, "<td>{value}</td>")?,
+      }
+
+      writeln!(body, "</tr>")?;
+    }
+
+    writeln!(body, "</table>")?;
+    Self::print_document(&details.heading(), &body);
+    Ok(())
+  }
+
+  fn print_scan(&self, mut scan: ScanResults, imdb_url: &Url, query: ImdbQuery) -> Res<()> {
+    let title = match query {
+      ImdbQuery::Series => "TVrank: Series",
+      _ => "TVrank: Movies",
+    };
+
+    let mut body = String::new();
+
+    if !scan.at_least_one {
+      writeln!(body, "<p>No valid directory names</p>")?;
+      Self::print_document(title, &body);
+      return Ok(());
+    }
+
+    let unmatched = std::mem::take(&mut scan.unmatched);
+    let (ambiguous, not_found): (Vec<_>, Vec<_>) =
+      unmatched.into_iter().partition(|(matches, _)| !matches.is_empty());
+
+    self.write_scan_summary(&mut body, &scan, ambiguous.len(), not_found.len())?;
+
+    if scan.at_least_one_matched {
+      self.write_results(&mut body, scan.results, imdb_url, query, None)?;
+    } else {
+      writeln!(body, "<p>None of the directories matched any titles</p>")?;
+    }
+
+    if !ambiguous.is_empty() {
+      writeln!(body, "<h2>Directories Matching Several Titles</h2>")?;
+
+      for (mut matches, search_terms) in ambiguous {
+        writeln!(body, "<h3>{}</h3>", escape_html(&search_terms))?;
+        let titles: Vec<_> = matches.page().iter().map(|&title| (None, title)).collect();
+        self.write_table(&mut body, &titles, imdb_url)?;
+      }
+    }
+
+    if !not_found.is_empty() {
+      writeln!(body, "<h2>Directories Not Matching Any Title</h2>")?;
+      writeln!(body, "<ul>")?;
+
+      for (_, search_terms) in not_found {
+        writeln!(body, "<li>{}</li>", escape_html(&search_terms))?;
+      }
+
+      writeln!(body, "</ul>")?;
+    }
+
+    Self::print_document(title, &body);
+    Ok(())
+  }
+}
+
+fn table_format() -> format::TableFormat {
+  format::FormatBuilder::new()
+    .column_separator('│')
+    .borders('│')
+    .padding(1, 1)
+    .build()
+}
+
+fn create_table(color: bool, columns: &Columns, widths: &[usize]) -> Table {
+  let mut table = Table::new();
+  table.set_format(table_format());
+
+  #[macro_export]
+  macro_rules! make_bold {
+    ($title: expr, $color: expr) => {
+      match $color {
+        true => Cell::new($title).with_style(Attr::Bold),
+        false => Cell::new($title),
+      }
+    };
+  }
+
+  let header = columns
+    .iter()
+    .zip(widths)
+    .map(|(spec, &width)| make_bold!(&truncate(spec.column.header(), width), color))
+    .collect();
+
+  table.add_row(Row::new(header));
+
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::print::{
+    escape_html, escape_markdown, CsvPrinter, HtmlPrinter, MarkdownPrinter, RatingThresholds,
+  };
+  use crate::testing::imdb;
+  use reqwest::Url;
+  use tvrank::imdb::{ImdbQuery, ImdbResults, ImdbSortOrder, ImdbTitleId};
+
+  #[test]
+  fn test_csv_record() {
+    let dir = tempfile::tempdir().unwrap();
+    let imdb = imdb(dir.path());
+    let imdb_url = Url::parse("https://www.imdb.com/title/").unwrap();
+    let title_id = ImdbTitleId::try_from("tt0000009").unwrap();
+
+    let write = |delimiter| {
+      let mut results = ImdbResults::new(ImdbSortOrder::default());
+      results.extend(imdb.by_id(&title_id, ImdbQuery::Movies));
+
+      let mut out = Vec::new();
+      CsvPrinter::new(delimiter)
+        .write_results(&mut out, Some(results), None, &imdb_url)
+        .unwrap();
+      String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!(
+      write(b','),
+      "Primary Title,Original Title,Year,Rating,Votes,Runtime (minutes),Genres,Type,IMDB ID,IMDB Link\n\
+       \"The Good, the \"\"Bad\"\" and the Ugly\",\"Il buono, il brutto, il cattivo\",1966,88,800000,178,\
+       Western,Movie,tt0000009,https://www.imdb.com/title/tt0000009\n"
+    );
+    assert_eq!(
+      write(b'\t'),
+      "Primary Title\tOriginal Title\tYear\tRating\tVotes\tRuntime (minutes)\tGenres\tType\tIMDB ID\tIMDB Link\n\
+       \"The Good, the \"\"Bad\"\" and the Ugly\"\tIl buono, il brutto, il cattivo\t1966\t88\t800000\t178\t\
+       Western\tMovie\ttt0000009\thttps://www.imdb.com/title/tt0000009\n"
+    );
+  }
+
+  #[test]
+  fn test_csv_quoting() {
+    let write = |delimiter| {
+      let mut writer = CsvPrinter::new(delimiter).writer(Vec::new());
+      writer.write_record(["tab\there", "new\nline", "com,ma", "\"quoted\""]).unwrap();
+      String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    };
+
+    assert_eq!(write(b','), "tab\there,\"new\nline\",\"com,ma\",\"\"\"quoted\"\"\"\n");
+    assert_eq!(write(b'\t'), "\"tab\there\"\t\"new\nline\"\tcom,ma\t\"\"\"quoted\"\"\"\n");
+  }
+
+  #[test]
+  fn test_escape_markdown() {
+    assert_eq!(escape_markdown("Alien"), "Alien");
+    assert_eq!(escape_markdown("a|b"), "a\\|b");
+    assert_eq!(escape_markdown("[link](url)"), "\\[link\\](url)");
+    assert_eq!(escape_markdown("*bold* _it_ `code`"), "\\*bold\\* \\_it\\_ \\`code\\`");
+    assert_eq!(escape_markdown("<b> & \"q\" 'q'"), "\\<b\\> & \"q\" 'q'");
+    assert_eq!(escape_markdown("back\\slash"), "back\\\\slash");
+    assert_eq!(escape_markdown("two\nlines\r\nhere"), "two lines here");
+  }
+
+  #[test]
+  fn test_escape_html() {
+    assert_eq!(escape_html("Alien"), "Alien");
+    assert_eq!(escape_html("<b>Tom & Jerry</b>"), "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;");
+    assert_eq!(escape_html("\"double\" 'single'"), "&quot;double&quot; &#39;single&#39;");
+    assert_eq!(escape_html("a|b [c] *d* _e_"), "a|b [c] *d* _e_");
+  }
+
+  #[test]
+  fn test_report_tables() {
+    let dir = tempfile::tempdir().unwrap();
+    let imdb = imdb(dir.path());
+    let imdb_url = Url::parse("https://www.imdb.com/title/").unwrap();
+    let ratings = RatingThresholds { good: 90, average: 86 };
+
+    let title = |id| imdb.by_id(&ImdbTitleId::try_from(id).unwrap(), ImdbQuery::All).unwrap();
+    let titles = [
+      (Some("good & <bad>\nugly"), title("tt0000009")),
+      (Some("wire"), title("tt0000004")),
+      (Some("alien"), title("tt0000001")),
+      (None, title("tt0000003")),
+    ];
+
+    let mut out = String::new();
+    MarkdownPrinter::new(ratings).write_table(&mut out, &titles, &imdb_url).unwrap();
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(
+      lines[0],
+      "| Query | Title | Original Title | Year | Rating | Votes | Runtime | Genres | Type | IMDB ID |"
+    );
+    assert_eq!(
+      lines[2],
+      "| good & \\<bad\\> ugly | [The Good, the \"Bad\" and the Ugly](https://www.imdb.com/title/tt0000009) \
+       | Il buono, il brutto, il cattivo | 1966 | 🟡 88/100 | 800000 | 2h 58m | Western | Movie | tt0000009 |"
+    );
+    assert!(lines[3].contains("| 🟢 93/100 |"));
+    assert!(lines[4].contains("| 🔴 85/100 |"));
+    assert!(lines[5].starts_with("|  | [Alien](https://www.imdb.com/title/tt0000003) |"));
+    assert!(lines[5].contains("| 2030 |  |  |"));
+
+    let mut out = String::new();
+    HtmlPrinter::new(ratings).write_table(&mut out, &titles, &imdb_url).unwrap();
+    assert!(out.starts_with("<table>\n<tr><th>Query</th><th>Title</th><th>Original Title</th>"));
+    assert!(out.contains(
+      "<tr><td>good &amp; &lt;bad&gt;\nugly</td>\
+       <td><a href=\"https://www.imdb.com/title/tt0000009\">The Good, the &quot;Bad&quot; and the Ugly</a></td>\
+       <td>Il buono, il brutto, il cattivo</td><td>1966</td><td class=\"yellow\">88/100</td><td>800000</td>\
+       <td>2h 58m</td><td>Western</td><td>Movie</td><td>tt0000009</td></tr>\n"
+    ));
+    assert!(out.contains("<td class=\"green\">93/100</td>"));
+    assert!(out.contains("<td class=\"red\">85/100</td>"));
+    assert!(out.contains(
+      "<tr><td></td><td><a href=\"https://www.imdb.com/title/tt0000003\">Alien</a></td>\
+       <td></td><td>2030</td><td></td><td></td>"
+    ));
+    assert!(out.ends_with("</table>\n"));
+  }
 }
@@ -0,0 +1,230 @@
+#![warn(clippy::all)]
+
+use std::error::Error;
+use std::fmt;
+use std::ops::Bound;
+use std::str::FromStr;
+use tvrank::imdb::{ImdbFilter, ImdbGenre, ImdbTitleType};
+
+/// A search query with the inline filters (e.g. `genre:sci-fi year:1995..2005`) taken out.
+pub struct SearchQuery {
+  /// What remains of the query once the filters have been removed.
+  pub terms: String,
+  /// The filters found in the query.
+  pub filter: ImdbFilter,
+  /// Whether any filters were found in the query.
+  pub has_filters: bool,
+}
+
+/// Error type pointing at the token of a search query that could not be parsed.
+#[derive(Debug)]
+pub struct QueryErr {
+  input: String,
+  start: usize,
+  len: usize,
+  msg: String,
+}
+
+impl QueryErr {
+  fn new(input: &str, token: &str, msg: String) -> Self {
+    let start = token.as_ptr() as usize - input.as_ptr() as usize;
+    Self { input: input.to_owned(), start, len: token.len(), msg }
+  }
+}
+
+impl fmt::Display for QueryErr {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let padding = self.input[..self.start].chars().count();
+    let width = self.input[self.start..self.start + self.len].chars().count();
+    writeln!(f, "{}", self.msg)?;
+    writeln!(f, "  {}", self.input)?;
+    write!(f, "  {}{}", " ".repeat(padding), "^".repeat(width))
+  }
+}
+
+impl Error for QueryErr {}
+
+/// Parse a numeric range such as `70`, `>=70`, `<110`, `1995..2005`, `1995..` or `..2005`.
+///
+/// Both ends of a `FROM..TO` range are inclusive.
+fn parse_range<T: FromStr>(value: &str) -> Option<(Bound<T>, Bound<T>)> {
+  fn bound<T: FromStr>(value: &str, bound: fn(T) -> Bound<T>) -> Option<Bound<T>> {
+    if value.is_empty() {
+      Some(Bound::Unbounded)
+    } else {
+      value.parse().ok().map(bound)
+    }
+  }
+
+  if let Some((from, to)) = value.split_once("..") {
+    if from.is_empty() && to.is_empty() {
+      return None;
+    }
+
+    Some((bound(from, Bound::Included)?, bound(to, Bound::Included)?))
+  } else if let Some(value) = value.strip_prefix(">=") {
+    Some((Bound::Included(value.parse().ok()?), Bound::Unbounded))
+  } else if let Some(value) = value.strip_prefix("<=") {
+    Some((Bound::Unbounded, Bound::Included(value.parse().ok()?)))
+  } else if let Some(value) = value.strip_prefix('>') {
+    Some((Bound::Excluded(value.parse().ok()?), Bound::Unbounded))
+  } else if let Some(value) = value.strip_prefix('<') {
+    Some((Bound::Unbounded, Bound::Excluded(value.parse().ok()?)))
+  } else {
+    let value = value.strip_prefix('=').unwrap_or(value);
+    Some((Bound::Included(value.parse().ok()?), Bound::Included(value.parse().ok()?)))
+  }
+}
+
+/// Take the inline filters out of a search query.
+///
+/// Filters are tokens of the form `KEY:VALUE`, where `KEY` is one of `genre`, `type`,
+/// `year`, `rating`, `votes` or `runtime`. Genres and types can be given as comma-separated
+/// lists. Years, ratings, votes and runtimes are given as numbers or ranges, e.g. `2001`,
+/// `1995..2005`, `>=70` or `<120`. Tokens with any other key, as well as tokens that are part
+/// of a quoted phrase, are kept as search terms, so that titles like "Re:Zero" can still be
+/// searched for.
+///
+/// # Arguments
+///
+/// * `input` - The search query.
+pub fn parse_search_query(input: &str) -> Result<SearchQuery, QueryErr> {
+  let mut terms = Vec::new();
+  let mut filter = ImdbFilter::default();
+  let mut has_filters = false;
+  let mut in_phrase = false;
+
+  for token in input.split_whitespace() {
+    let is_quoted = in_phrase || token.contains('"');
+    in_phrase ^= token.matches('"').count() % 2 == 1;
+
+    if is_quoted {
+      terms.push(token);
+      continue;
+    }
+
+    let (key, value) = match token.split_once(':') {
+      Some((key, value)) => (key.to_lowercase(), value),
+      None => {
+        terms.push(token);
+        continue;
+      }
+    };
+
+    let invalid = |what: &str, value: &str| {
+      QueryErr::new(input, token, format!("Invalid {what} `{value}` in `{key}` filter"))
+    };
+
+    filter = match key.as_str() {
+      "genre" => {
+        for genre in value.split(',') {
+          let genre = ImdbGenre::from_name(genre).ok_or_else(|| invalid("genre", genre))?;
+          filter = filter.with_genre(genre);
+        }
+        filter
+      }
+      "type" => {
+        for title_type in value.split(',') {
+          let title_type =
+            ImdbTitleType::from_name(title_type).ok_or_else(|| invalid("title type", title_type))?;
+          filter = filter.with_title_type(title_type);
+        }
+        filter
+      }
+      "year" => filter.with_years(parse_range::<u16>(value).ok_or_else(|| invalid("year range", value))?),
+      "rating" => filter.with_rating(parse_range::<u8>(value).ok_or_else(|| invalid("rating range", value))?),
+      "votes" => filter.with_votes(parse_range::<u32>(value).ok_or_else(|| invalid("votes range", value))?),
+      "runtime" => {
+        filter.with_runtime(parse_range::<u16>(value).ok_or_else(|| invalid("runtime range", value))?)
+      }
+      _ => {
+        terms.push(token);
+        continue;
+      }
+    };
+
+    has_filters = true;
+  }
+
+  Ok(SearchQuery { terms: terms.join(" "), filter, has_filters })
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::query::{parse_range, parse_search_query};
+  use std::ops::Bound::{Excluded, Included, Unbounded};
+  use tvrank::imdb::{ImdbFilter, ImdbGenre, ImdbTitleType};
+
+  #[test]
+  fn test_parse_range() {
+    assert_eq!(parse_range::<u16>("2001"), Some((Included(2001), Included(2001))));
+    assert_eq!(parse_range::<u16>("=2001"), Some((Included(2001), Included(2001))));
+    assert_eq!(parse_range::<u16>("1995..2005"), Some((Included(1995), Included(2005))));
+    assert_eq!(parse_range::<u16>("1995.."), Some((Included(1995), Unbounded)));
+    assert_eq!(parse_range::<u16>("..2005"), Some((Unbounded, Included(2005))));
+    assert_eq!(parse_range::<u8>(">=70"), Some((Included(70), Unbounded)));
+    assert_eq!(parse_range::<u8>("<=70"), Some((Unbounded, Included(70))));
+    assert_eq!(parse_range::<u8>(">70"), Some((Excluded(70), Unbounded)));
+    assert_eq!(parse_range::<u8>("<70"), Some((Unbounded, Excluded(70))));
+
+    assert_eq!(parse_range::<u16>(""), None);
+    assert_eq!(parse_range::<u16>(".."), None);
+    assert_eq!(parse_range::<u16>("1995..soon"), None);
+    assert_eq!(parse_range::<u16>(">="), None);
+    assert_eq!(parse_range::<u8>("300"), None);
+    assert_eq!(parse_range::<u8>("-1"), None);
+  }
+
+  #[test]
+  fn test_parse_search_query() {
+    let query = parse_search_query("the matrix").unwrap();
+    assert_eq!(query.terms, "the matrix");
+    assert!(!query.has_filters);
+
+    let query = parse_search_query(
+      "Genre:Sci-Fi,drama alien type:movie year:1975..1990 rating:>=70 votes:1000.. runtime:<150",
+    )
+    .unwrap();
+    assert_eq!(query.terms, "alien");
+    assert!(query.has_filters);
+
+    let filter = ImdbFilter::default()
+      .with_genre(ImdbGenre::SciFi)
+      .with_genre(ImdbGenre::Drama)
+      .with_title_type(ImdbTitleType::Movie)
+      .with_years((Included(1975), Included(1990)))
+      .with_rating((Included(70), Unbounded))
+      .with_votes((Included(1000), Unbounded))
+      .with_runtime((Unbounded, Excluded(150)));
+    assert_eq!(format!("{:?}", query.filter), format!("{filter:?}"));
+
+    // Unknown keys and quoted phrases are kept as search terms.
+    let query = parse_search_query("re:zero \"genre:horror night\" year:2016").unwrap();
+    assert_eq!(query.terms, "re:zero \"genre:horror night\"");
+    assert!(query.has_filters);
+  }
+
+  #[test]
+  fn test_query_errors() {
+    let message = |input| parse_search_query(input).err().unwrap().to_string();
+
+    assert_eq!(
+      message("alien year:soon"),
+      "Invalid year range `soon` in `year` filter\n  alien year:soon\n        ^^^^^^^^^"
+    );
+    assert_eq!(
+      message("genre:horror,nope alien"),
+      "Invalid genre `nope` in `genre` filter\n  genre:horror,nope alien\n  ^^^^^^^^^^^^^^^^^"
+    );
+    assert_eq!(
+      message("alien type:film"),
+      "Invalid title type `film` in `type` filter\n  alien type:film\n        ^^^^^^^^^"
+    );
+    assert_eq!(
+      message("amélie rating:>=300"),
+      "Invalid rating range `>=300` in `rating` filter\n  amélie rating:>=300\n         ^^^^^^^^^^^^"
+    );
+    assert!(message("votes:..").starts_with("Invalid votes range `..`"));
+    assert!(message("runtime:").starts_with("Invalid runtime range ``"));
+  }
+}
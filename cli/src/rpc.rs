@@ -0,0 +1,446 @@
+#![warn(clippy::all)]
+
+use crate::params::{parse_params, QueryParams};
+use crate::print::to_json_merged;
+use crate::{imdb_title_results, imdb_top_results};
+use humantime::format_duration;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+use std::time::Instant;
+use tvrank::imdb::{Imdb, ImdbKeywords, ImdbQuery, ImdbResults, ImdbSortOrder, ImdbTitleId};
+use tvrank::utils::result::Res;
+
+/// A JSON-RPC 2.0 error object.
+#[derive(Debug, Serialize)]
+struct RpcErr {
+  code: i64,
+  message: String,
+}
+
+impl RpcErr {
+  fn parse_error(e: impl Error) -> Self {
+    Self { code: -32700, message: format!("Parse error: {e}") }
+  }
+
+  fn invalid_request(message: impl Into<String>) -> Self {
+    Self { code: -32600, message: message.into() }
+  }
+
+  fn method_not_found(method: &str) -> Self {
+    Self { code: -32601, message: format!("Unknown method `{method}`") }
+  }
+
+  fn invalid_params(message: impl Into<String>) -> Self {
+    Self { code: -32602, message: message.into() }
+  }
+
+  fn unknown_imdb_id(id: &str) -> Self {
+    Self { code: -32001, message: format!("Id `{id}` was not found") }
+  }
+}
+
+impl From<Box<dyn Error>> for RpcErr {
+  fn from(e: Box<dyn Error>) -> Self {
+    Self::invalid_params(e.to_string())
+  }
+}
+
+/// A JSON-RPC 2.0 request, or a notification if it has no `id`.
+#[derive(Deserialize)]
+struct Request {
+  jsonrpc: String,
+  #[serde(default)]
+  id: Option<Value>,
+  method: String,
+  #[serde(default)]
+  params: Option<Value>,
+}
+
+/// Parameters of the `by_id` method.
+#[derive(Debug, clap::Parser)]
+struct IdParams {
+  /// The unique IMDB ID ("ttXXXXX")
+  #[clap(long)]
+  id: String,
+}
+
+/// Parameters of the `by_title` method.
+#[derive(Debug, clap::Parser)]
+struct TitleParams {
+  /// The exact title to look up
+  #[clap(long)]
+  title: String,
+
+  /// Only include titles released in YEAR
+  #[clap(long, value_name = "YEAR")]
+  year: Option<u16>,
+
+  #[clap(flatten)]
+  query: QueryParams,
+}
+
+/// Parameters of the `by_keywords` method.
+#[derive(Debug, clap::Parser)]
+struct KeywordsParams {
+  /// The keywords to look up, as with the search subcommand
+  #[clap(long)]
+  keywords: String,
+
+  /// Only include titles released in YEAR
+  #[clap(long, value_name = "YEAR")]
+  year: Option<u16>,
+
+  #[clap(flatten)]
+  query: QueryParams,
+}
+
+/// Convert the named parameters of a request to the parameters accepted by [`parse_params`].
+///
+/// Strings and numbers are passed as values, `true` as a flag and arrays as repeated
+/// parameters, while `false` and `null` are left out.
+///
+/// # Arguments
+///
+/// * `params` - The parameters of the request.
+fn to_params(params: Option<Value>) -> Result<Vec<(String, Option<String>)>, RpcErr> {
+  let params = match params {
+    None | Some(Value::Null) => return Ok(Vec::new()),
+    Some(Value::Object(params)) => params,
+    Some(_) => return Err(RpcErr::invalid_params("Parameters must be given by name")),
+  };
+
+  let mut result = Vec::new();
+
+  for (name, value) in params {
+    let values = match value {
+      Value::Array(values) => values,
+      value => vec![value],
+    };
+
+    for value in values {
+      match value {
+        Value::Bool(true) => result.push((name.clone(), None)),
+        Value::Bool(false) | Value::Null => {}
+        Value::String(value) => result.push((name.clone(), Some(value))),
+        Value::Number(value) => result.push((name.clone(), Some(value.to_string()))),
+        Value::Array(_) | Value::Object(_) => {
+          return Err(RpcErr::invalid_params(format!("Unsupported value for parameter `{name}`")))
+        }
+      }
+    }
+  }
+
+  Ok(result)
+}
+
+struct Service<'a> {
+  imdb: &'a Imdb,
+}
+
+impl Service<'_> {
+  fn by_id(&self, params: Option<Value>) -> Result<Value, RpcErr> {
+    let params: IdParams = parse_params(to_params(params)?)?;
+    let title_id = ImdbTitleId::try_from(params.id.as_str())?;
+
+    match self.imdb.by_id(&title_id, ImdbQuery::All) {
+      Some(title) => {
+        let mut results = ImdbResults::new(ImdbSortOrder::default());
+        results.push(title);
+        Ok(to_json_merged(results)?)
+      }
+      None => Err(RpcErr::unknown_imdb_id(&params.id)),
+    }
+  }
+
+  fn by_title(&self, params: Option<Value>) -> Result<Value, RpcErr> {
+    let params: TitleParams = parse_params(to_params(params)?)?;
+    let query = &params.query;
+    let filter = query.filter_opts.to_filter();
+    let title = (params.title.as_str(), params.year);
+
+    Ok(
+      query
+        .to_json(|kind| Ok(imdb_title_results(self.imdb, title, None, &filter, kind, &query.search_opts)))?,
+    )
+  }
+
+  fn by_keywords(&self, params: Option<Value>) -> Result<Value, RpcErr> {
+    let params: KeywordsParams = parse_params(to_params(params)?)?;
+    let query = &params.query;
+    let filter = query.filter_opts.to_filter();
+    let keywords = ImdbKeywords::parse(&params.keywords)?;
+    let title = (params.keywords.as_str(), params.year);
+
+    Ok(query.to_json(|kind| {
+      Ok(imdb_title_results(self.imdb, title, Some(&keywords), &filter, kind, &query.search_opts))
+    })?)
+  }
+
+  fn top(&self, params: Option<Value>) -> Result<Value, RpcErr> {
+    let params: QueryParams = parse_params(to_params(params)?)?;
+    let filter = params.filter_opts.to_filter();
+    Ok(params.to_json(|kind| Ok(imdb_top_results(self.imdb, &filter, kind, &params.search_opts)))?)
+  }
+
+  fn call(&self, method: &str, params: Option<Value>) -> Result<Value, RpcErr> {
+    match method {
+      "by_id" => self.by_id(params),
+      "by_title" => self.by_title(params),
+      "by_keywords" => self.by_keywords(params),
+      "top" => self.top(params),
+      _ => Err(RpcErr::method_not_found(method)),
+    }
+  }
+
+  /// Handle a single line of input, returning the response to it, if any.
+  ///
+  /// A batch of requests is answered with an array of responses, leaving out notifications,
+  /// or with no response at all if it only holds notifications.
+  ///
+  /// # Arguments
+  ///
+  /// * `line` - The line of input, holding a JSON-RPC request, notification or batch.
+  fn handle(&self, line: &str) -> Option<Value> {
+    let request = match serde_json::from_str::<Value>(line) {
+      Ok(request) => request,
+      Err(e) => return Some(response(Value::Null, Err(RpcErr::parse_error(e)))),
+    };
+
+    match request {
+      Value::Array(batch) if batch.is_empty() => {
+        Some(response(Value::Null, Err(RpcErr::invalid_request("Empty batch"))))
+      }
+      Value::Array(batch) => {
+        let responses: Vec<Value> =
+          batch.into_iter().filter_map(|request| self.handle_request(request)).collect();
+        (!responses.is_empty()).then_some(Value::Array(responses))
+      }
+      request => self.handle_request(request),
+    }
+  }
+
+  /// Handle a single request, returning the response to it, if any.
+  ///
+  /// # Arguments
+  ///
+  /// * `request` - The JSON-RPC request or notification.
+  fn handle_request(&self, request: Value) -> Option<Value> {
+    let request = match parse_request(request) {
+      Ok(request) => request,
+      Err((id, e)) => return Some(response(id, Err(e))),
+    };
+
+    let start_time = Instant::now();
+    let result = self.call(&request.method, request.params);
+    info!("{} took {}", request.method, format_duration(Instant::now().duration_since(start_time)));
+
+    // Notifications do not get a response.
+    let id = request.id?;
+    Some(response(id, result))
+  }
+}
+
+/// Parse a request, or return the error along with the `id` of the request, if any.
+///
+/// # Arguments
+///
+/// * `request` - The JSON-RPC request or notification.
+fn parse_request(request: Value) -> Result<Request, (Value, RpcErr)> {
+  let id = request.get("id").cloned().unwrap_or(Value::Null);
+
+  match serde_json::from_value::<Request>(request) {
+    Ok(request) if request.jsonrpc == "2.0" => Ok(request),
+    Ok(_) => Err((id, RpcErr::invalid_request("Only JSON-RPC 2.0 is supported"))),
+    Err(e) => Err((id, RpcErr::invalid_request(format!("Invalid request: {e}")))),
+  }
+}
+
+/// Build the response to a request.
+///
+/// # Arguments
+///
+/// * `id` - The `id` of the request.
+/// * `result` - The result of the request.
+fn response(id: Value, result: Result<Value, RpcErr>) -> Value {
+  match result {
+    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+    Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": e }),
+  }
+}
+
+pub fn run(imdb: &Imdb) -> Res<()> {
+  let service = Service { imdb };
+  let stdin = io::stdin();
+  let stdout = io::stdout();
+
+  for line in stdin.lock().lines() {
+    let line = line?;
+
+    if line.trim().is_empty() {
+      continue;
+    }
+
+    debug!("Received request: {line}");
+
+    if let Some(response) = service.handle(&line) {
+      let mut stdout = stdout.lock();
+      writeln!(stdout, "{response}")?;
+      stdout.flush()?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::rpc::{parse_request, to_params, Service};
+  use crate::testing::imdb;
+  use serde_json::{json, Value};
+
+  fn error_code(response: &Value) -> i64 {
+    response["error"]["code"].as_i64().unwrap()
+  }
+
+  #[test]
+  fn test_to_params() {
+    let params = to_params(Some(json!({
+      "title": "alien",
+      "year": 1979,
+      "movies": true,
+      "series": false,
+      "top": null,
+      "genre": ["horror", "sci-fi"],
+    })))
+    .unwrap();
+
+    let param = |name: &str, value: Option<&str>| (name.to_owned(), value.map(str::to_owned));
+    assert_eq!(
+      params,
+      [
+        param("genre", Some("horror")),
+        param("genre", Some("sci-fi")),
+        param("movies", None),
+        param("title", Some("alien")),
+        param("year", Some("1979")),
+      ]
+    );
+
+    assert!(to_params(None).unwrap().is_empty());
+    assert!(to_params(Some(Value::Null)).unwrap().is_empty());
+    assert_eq!(to_params(Some(json!(["alien"]))).unwrap_err().code, -32602);
+    assert_eq!(to_params(Some(json!({ "genre": [["horror"]] }))).unwrap_err().code, -32602);
+    assert_eq!(to_params(Some(json!({ "title": { "name": "alien" } }))).unwrap_err().code, -32602);
+  }
+
+  #[test]
+  fn test_parse_request() {
+    let request = parse_request(json!({ "jsonrpc": "2.0", "id": 1, "method": "top" })).unwrap();
+    assert_eq!(request.id, Some(json!(1)));
+    assert_eq!(request.method, "top");
+    assert!(request.params.is_none());
+
+    let request = parse_request(json!({ "jsonrpc": "2.0", "method": "top", "params": {} })).unwrap();
+    assert!(request.id.is_none());
+
+    let (id, e) = parse_request(json!({ "jsonrpc": "1.0", "id": 2, "method": "top" }))
+      .err()
+      .unwrap();
+    assert_eq!((id, e.code), (json!(2), -32600));
+
+    let (id, e) = parse_request(json!({ "id": 3, "method": "top" })).err().unwrap();
+    assert_eq!((id, e.code), (json!(3), -32600));
+
+    let (id, e) = parse_request(json!({ "jsonrpc": "2.0", "id": 4 })).err().unwrap();
+    assert_eq!((id, e.code), (json!(4), -32600));
+
+    let (id, e) = parse_request(json!("top")).err().unwrap();
+    assert_eq!((id, e.code), (Value::Null, -32600));
+  }
+
+  #[test]
+  fn test_handle() {
+    let dir = tempfile::tempdir().unwrap();
+    let imdb = imdb(dir.path());
+    let service = Service { imdb: &imdb };
+    let handle = |line: &str| service.handle(line);
+
+    let response =
+      handle(r#"{"jsonrpc": "2.0", "id": 1, "method": "by_id", "params": {"id": "tt0000004"}}"#).unwrap();
+    assert_eq!(response["jsonrpc"], "2.0");
+    assert_eq!(response["id"], 1);
+    assert_eq!(response["result"]["titles"][0]["primary_title"], "The Wire");
+    assert!(response.get("error").is_none());
+
+    let response = handle(
+      r#"{"jsonrpc": "2.0", "id": "a", "method": "by_title", "params": {"title": "alien", "year": 1979, "movies": true}}"#,
+    )
+    .unwrap();
+    assert_eq!(response["id"], "a");
+    assert_eq!(response["result"]["movies"][0]["title_id"], "tt0000001");
+    assert_eq!(response["result"]["series"], Value::Null);
+
+    let response = handle(
+      r#"{"jsonrpc": "2.0", "id": 2, "method": "by_keywords", "params": {"keywords": "-aliens alien", "merge": true}}"#,
+    )
+    .unwrap();
+    let ids: Vec<_> = response["result"]["titles"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|title| &title["title_id"])
+      .collect();
+    assert_eq!(ids, ["tt0000001", "tt0000003"]);
+
+    let response =
+      handle(r#"{"jsonrpc": "2.0", "id": 3, "method": "top", "params": {"series": true, "top": 1}}"#)
+        .unwrap();
+    assert_eq!(response["result"]["series"][0]["title_id"], "tt0000004");
+
+    // Errors.
+    let response = handle(r#"{"jsonrpc": "2.0", "id": 4, "method""#).unwrap();
+    assert_eq!((error_code(&response), &response["id"]), (-32700, &Value::Null));
+    let response = handle(r#"{"jsonrpc": "1.0", "id": 5, "method": "top"}"#).unwrap();
+    assert_eq!((error_code(&response), &response["id"]), (-32600, &json!(5)));
+    let response = handle(r#"{"jsonrpc": "2.0", "id": 6, "method": "nope"}"#).unwrap();
+    assert_eq!((error_code(&response), &response["id"]), (-32601, &json!(6)));
+    let response = handle(r#"{"jsonrpc": "2.0", "id": 7, "method": "by_id", "params": {}}"#).unwrap();
+    assert_eq!(error_code(&response), -32602);
+    let response =
+      handle(r#"{"jsonrpc": "2.0", "id": 8, "method": "by_id", "params": ["tt0000004"]}"#).unwrap();
+    assert_eq!(error_code(&response), -32602);
+    let response =
+      handle(r#"{"jsonrpc": "2.0", "id": 9, "method": "top", "params": {"top": "many"}}"#).unwrap();
+    assert_eq!(error_code(&response), -32602);
+    let response =
+      handle(r#"{"jsonrpc": "2.0", "id": 10, "method": "by_id", "params": {"id": "tt0000005"}}"#).unwrap();
+    assert_eq!(error_code(&response), -32001);
+    assert!(response.get("result").is_none());
+
+    // Notifications.
+    assert!(handle(r#"{"jsonrpc": "2.0", "method": "top"}"#).is_none());
+    assert!(handle(r#"{"jsonrpc": "2.0", "method": "nope"}"#).is_none());
+
+    // Batches.
+    let response = handle(
+      r#"[
+        {"jsonrpc": "2.0", "id": 1, "method": "by_id", "params": {"id": "tt0000001"}},
+        {"jsonrpc": "2.0", "method": "top"},
+        {"jsonrpc": "2.0", "id": 2, "method": "nope"},
+        42
+      ]"#,
+    )
+    .unwrap();
+    let responses = response.as_array().unwrap();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["result"]["titles"][0]["title_id"], "tt0000001");
+    assert_eq!((error_code(&responses[1]), &responses[1]["id"]), (-32601, &json!(2)));
+    assert_eq!((error_code(&responses[2]), &responses[2]["id"]), (-32600, &Value::Null));
+
+    assert!(handle(r#"[{"jsonrpc": "2.0", "method": "top"}]"#).is_none());
+    assert_eq!(error_code(&handle("[]").unwrap()), -32600);
+  }
+}
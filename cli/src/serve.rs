@@ -0,0 +1,345 @@
+#![warn(clippy::all)]
+
+use crate::params::{parse_params, QueryParams};
+use crate::print::{to_json, to_json_merged};
+use crate::query::parse_search_query;
+use crate::{
+  imdb_movies_dir_results, imdb_search_results, imdb_series_dir_results, imdb_top_results, SearchOpts,
+};
+use derive_more::Display;
+use humantime::format_duration;
+use log::{debug, info, warn};
+use reqwest::Url;
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Instant;
+use tiny_http::{Header, Method, Request, Response, Server};
+use tvrank::imdb::{Imdb, ImdbQuery, ImdbResults, ImdbSortOrder, ImdbTitleId};
+use tvrank::utils::result::Res;
+
+#[derive(Debug, Display)]
+#[display(fmt = "{}")]
+enum ServeErr {
+  #[display(fmt = "Unknown endpoint `{}`", _0)]
+  UnknownEndpoint(String),
+  #[display(fmt = "Id `{}` was not found", _0)]
+  UnknownImdbId(String),
+  #[display(fmt = "Only GET requests are supported")]
+  MethodNotAllowed,
+  #[display(fmt = "{}", _0)]
+  BadRequest(Box<dyn Error>),
+}
+
+impl Error for ServeErr {}
+
+impl From<Box<dyn Error>> for ServeErr {
+  fn from(e: Box<dyn Error>) -> Self {
+    ServeErr::BadRequest(e)
+  }
+}
+
+impl ServeErr {
+  fn bad_request(e: impl Error + 'static) -> Self {
+    ServeErr::BadRequest(Box::new(e))
+  }
+
+  fn status_code(&self) -> u16 {
+    match self {
+      ServeErr::UnknownEndpoint(_) | ServeErr::UnknownImdbId(_) => 404,
+      ServeErr::MethodNotAllowed => 405,
+      ServeErr::BadRequest(_) => 400,
+    }
+  }
+}
+
+/// Parameters of the `/search` endpoint.
+#[derive(Debug, clap::Parser)]
+struct SearchParams {
+  /// Search terms, as with the search subcommand
+  #[clap(long)]
+  q: String,
+
+  /// Match the given title exactly
+  #[clap(long)]
+  exact: bool,
+
+  /// Return movies and series together, as a single list
+  #[clap(long)]
+  merge: bool,
+
+  #[clap(flatten)]
+  search_opts: SearchOpts,
+}
+
+/// Parameters of the `/scan/movies` and `/scan/series` endpoints.
+#[derive(Debug, clap::Parser)]
+struct ScanParams {
+  /// The directory to scan, as with the scan-movies and scan-series subcommands
+  #[clap(long)]
+  dir: PathBuf,
+
+  #[clap(flatten)]
+  search_opts: SearchOpts,
+}
+
+/// Parse the query parameters of a request, see [`parse_params`].
+///
+/// Parameters without an `=` (e.g. `?exact`) are passed as flags, while those with an empty
+/// value (e.g. `?q=`) are passed as empty values.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the request.
+fn parse_query<P: clap::Parser>(url: &Url) -> Res<P> {
+  // The decoded pairs skip empty parameters, just like the raw ones below, so they line up.
+  let raw = url.query().unwrap_or_default().split('&').filter(|param| !param.is_empty());
+
+  parse_params(url.query_pairs().zip(raw).map(|((name, value), raw)| {
+    let value = raw.contains('=').then(|| value.into_owned());
+    (name.into_owned(), value)
+  }))
+}
+
+struct Service<'a> {
+  imdb: &'a Imdb,
+}
+
+impl Service<'_> {
+  fn search(&self, url: &Url) -> Result<serde_json::Value, ServeErr> {
+    let params: SearchParams = parse_query(url)?;
+    let query = parse_search_query(&params.q).map_err(ServeErr::bad_request)?;
+    let search = |kind| imdb_search_results(self.imdb, &query, params.exact, kind, &params.search_opts);
+
+    if params.merge {
+      let (results, _) = search(ImdbQuery::All)?;
+      Ok(to_json_merged(results)?)
+    } else {
+      let (movies, _) = search(ImdbQuery::Movies)?;
+      let (series, _) = search(ImdbQuery::Series)?;
+      Ok(to_json(Some(movies), Some(series))?)
+    }
+  }
+
+  fn title(&self, id: &str) -> Result<serde_json::Value, ServeErr> {
+    let title_id = ImdbTitleId::try_from(id)?;
+
+    match self.imdb.by_id(&title_id, ImdbQuery::All) {
+      Some(title) => {
+        let mut results = ImdbResults::new(ImdbSortOrder::default());
+        results.push(title);
+        Ok(to_json_merged(results)?)
+      }
+      None => Err(ServeErr::UnknownImdbId(id.to_owned())),
+    }
+  }
+
+  fn top(&self, url: &Url) -> Result<serde_json::Value, ServeErr> {
+    let params: QueryParams = parse_query(url)?;
+    let filter = params.filter_opts.to_filter();
+    Ok(params.to_json(|kind| Ok(imdb_top_results(self.imdb, &filter, kind, &params.search_opts)))?)
+  }
+
+  fn scan(&self, url: &Url, query: ImdbQuery) -> Result<serde_json::Value, ServeErr> {
+    let params: ScanParams = parse_query(url)?;
+
+    match query {
+      ImdbQuery::Series => {
+        let results = imdb_series_dir_results(
+          &params.dir,
+          self.imdb,
+          params.search_opts.results(params.search_opts.top),
+          false,
+          &mut |_, _| Ok(false),
+        )?;
+        Ok(to_json(None, Some(results.results))?)
+      }
+      _ => {
+        let results = imdb_movies_dir_results(
+          &params.dir,
+          self.imdb,
+          params.search_opts.results(params.search_opts.top),
+          false,
+          &mut |_, _| Ok(false),
+        )?;
+        Ok(to_json(Some(results.results), None)?)
+      }
+    }
+  }
+
+  fn route(&self, method: &Method, url: &Url) -> Result<serde_json::Value, ServeErr> {
+    if *method != Method::Get {
+      return Err(ServeErr::MethodNotAllowed);
+    }
+
+    let path = url.path().trim_end_matches('/');
+
+    match path {
+      "/search" => self.search(url),
+      "/top" => self.top(url),
+      "/scan/movies" => self.scan(url, ImdbQuery::Movies),
+      "/scan/series" => self.scan(url, ImdbQuery::Series),
+      _ => match path.strip_prefix("/title/") {
+        Some(id) => self.title(id),
+        None => Err(ServeErr::UnknownEndpoint(path.to_owned())),
+      },
+    }
+  }
+
+  fn respond(&self, request: Request) -> Res<()> {
+    let start_time = Instant::now();
+    let url = Url::parse("http://localhost")?.join(request.url())?;
+
+    let (status_code, body) = match self.route(request.method(), &url) {
+      Ok(body) => (200, body.to_string()),
+      Err(e) => (e.status_code(), serde_json::json!({ "error": e.to_string() }).to_string()),
+    };
+
+    info!(
+      "{} {} {status_code} in {}",
+      request.method(),
+      request.url(),
+      format_duration(Instant::now().duration_since(start_time))
+    );
+
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+      .map_err(|()| "Could not create the Content-Type header")?;
+    let response = Response::from_string(body)
+      .with_status_code(status_code)
+      .with_header(content_type);
+    request.respond(response)?;
+
+    Ok(())
+  }
+}
+
+/// Respond to the requests of a server until it is unblocked.
+///
+/// # Arguments
+///
+/// * `imdb` - The IMDB service to answer requests with.
+/// * `server` - The server to receive requests from.
+fn serve(imdb: &Imdb, server: &Server) {
+  let service = Service { imdb };
+
+  for request in server.incoming_requests() {
+    debug!("Received {} request for {}", request.method(), request.url());
+
+    if let Err(e) = service.respond(request) {
+      warn!("Could not respond to request: {e}");
+    }
+  }
+}
+
+pub fn run(imdb: &Imdb, bind: &str) -> Res<()> {
+  let server = Server::http(bind).map_err(|e| e as Box<dyn Error>)?;
+  eprintln!("Listening on http://{bind}");
+  serve(imdb, &server);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::serve::serve;
+  use crate::testing::{imdb, Unblock};
+  use reqwest::blocking::Client;
+  use reqwest::{StatusCode, Url};
+  use serde_json::{json, Value};
+  use std::fs;
+  use std::thread;
+  use tiny_http::Server;
+
+  #[test]
+  fn test_serve() {
+    let dir = tempfile::tempdir().unwrap();
+    let imdb = imdb(dir.path());
+
+    let movies_dir = dir.path().join("movies");
+    fs::create_dir_all(movies_dir.join("Alien (1979)")).unwrap();
+    fs::create_dir_all(movies_dir.join("Aliens (1986)")).unwrap();
+
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let url = Url::parse(&format!("http://{}/", server.server_addr())).unwrap();
+    let client = Client::new();
+
+    let get = |path: &str| {
+      let response = client.get(url.join(path).unwrap()).send().unwrap();
+      let content_type = response.headers()["Content-Type"].to_str().unwrap().to_owned();
+      assert_eq!(content_type, "application/json");
+      let status = response.status();
+      (status, serde_json::from_str::<Value>(&response.text().unwrap()).unwrap())
+    };
+
+    let ids = |titles: &Value| {
+      let titles = titles.as_array().unwrap();
+      titles
+        .iter()
+        .map(|title| title["title_id"].as_str().unwrap().to_owned())
+        .collect::<Vec<_>>()
+    };
+
+    thread::scope(|scope| {
+      scope.spawn(|| serve(&imdb, &server));
+      let _unblock = Unblock(&server);
+
+      let (status, body) = get("/search?q=alien");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["movies"]), ["tt0000001", "tt0000002", "tt0000003"]);
+      assert_eq!(body["series"], json!([]));
+
+      let alien = &body["movies"][0];
+      assert_eq!(alien["primary_title"], "Alien");
+      assert_eq!(alien["start_year"], 1979);
+      assert_eq!(alien["rating"], json!({ "rating": 85, "votes": 900_000 }));
+      assert_eq!(alien["genres"], json!(["Horror", "Sci-Fi"]));
+
+      let (status, body) = get("/search?q=alien%20(1979)&exact");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["movies"]), ["tt0000001"]);
+
+      let (status, body) = get("/search?q=-aliens%20alien&merge");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["titles"]), ["tt0000001", "tt0000003"]);
+
+      let (status, body) = get("/search/?q=wire&sort=year&top=1");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["series"]), ["tt0000004"]);
+
+      let (status, body) = get("/top?movies&min_year=1980&max_year=2000");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["movies"]), ["tt0000002"]);
+      assert_eq!(body["series"], Value::Null);
+
+      let (status, body) = get("/top?merge&top=2");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["titles"]).len(), 2);
+
+      let (status, body) = get("/title/tt0000004");
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(body["titles"][0]["primary_title"], "The Wire");
+
+      let (status, body) = get(&format!("/scan/movies?dir={}", movies_dir.display()));
+      assert_eq!(status, StatusCode::OK);
+      assert_eq!(ids(&body["movies"]), ["tt0000001", "tt0000002"]);
+      assert_eq!(body["series"], Value::Null);
+
+      let (status, body) = get("/title/tt0000005");
+      assert_eq!(status, StatusCode::NOT_FOUND);
+      assert_eq!(body, json!({ "error": "Id `tt0000005` was not found" }));
+
+      let (status, body) = get("/nope");
+      assert_eq!(status, StatusCode::NOT_FOUND);
+      assert_eq!(body, json!({ "error": "Unknown endpoint `/nope`" }));
+
+      for path in
+        ["/title/nope", "/search", "/search?q=alien&unknown", "/search?q=alien&exact=yes", "/top?top=x"]
+      {
+        let (status, body) = get(path);
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{path}");
+        assert!(body["error"].is_string(), "{path}");
+      }
+
+      let response = client.post(url.join("/top").unwrap()).send().unwrap();
+      assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    });
+  }
+}
@@ -0,0 +1,169 @@
+#![warn(clippy::all)]
+
+use crate::print::OutputFormat;
+use crate::query::parse_search_query;
+use crate::{create_output_printer, imdb_search_results, GeneralOpts, SearchOpts};
+use clap::ArgEnum;
+use humantime::format_duration;
+use log::{debug, warn};
+use reqwest::Url;
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+use std::path::Path;
+use std::time::Instant;
+use tvrank::imdb::{Imdb, ImdbQuery, ImdbSortOrder};
+use tvrank::utils::result::Res;
+
+const PROMPT: &str = "tvrank> ";
+
+const HELP: &str = "\
+Type search terms (e.g. `the matrix`, `alien (1979)` or `star wars genre:sci-fi rating:>=70`)
+to search for titles, or one of the following commands:
+
+  :movies          Only search movies
+  :series          Only search series
+  :both            Search movies and series, and display them separately (default)
+  :merge           Search movies and series, and display them together
+  :exact           Match search terms exactly
+  :keywords        Match search terms as keywords (default)
+  :top [N]         Only display the top N results, or all of them if N is not given
+  :offset N        Skip the first N results
+  :sort [KEYS]     Sort by KEYS (e.g. `year`, `votes:desc,year:asc`), or by rating if not given
+  :output FORMAT   Display results as json, ndjson, table, yaml, csv, tsv, markdown or html
+  :help            Display this help
+  :quit            Exit the shell";
+
+/// Which titles are searched and how they are displayed.
+#[derive(Clone, Copy)]
+enum Scope {
+  Both,
+  Movies,
+  Series,
+  Merged,
+}
+
+struct Shell<'a> {
+  imdb: &'a Imdb,
+  imdb_url: &'a Url,
+  general_opts: &'a GeneralOpts,
+  search_opts: SearchOpts,
+  scope: Scope,
+  exact: bool,
+}
+
+impl Shell<'_> {
+  /// Run a command (without the leading colon), returning false if the shell should exit.
+  fn command(&mut self, command: &str) -> Res<bool> {
+    let (name, arg) = match command.split_once(char::is_whitespace) {
+      Some((name, arg)) => (name, Some(arg.trim()).filter(|arg| !arg.is_empty())),
+      None => (command, None),
+    };
+
+    match (name, arg) {
+      ("q" | "quit" | "exit", None) => return Ok(false),
+      ("h" | "help", None) => println!("{HELP}"),
+      ("movies", None) => self.scope = Scope::Movies,
+      ("series", None) => self.scope = Scope::Series,
+      ("both", None) => self.scope = Scope::Both,
+      ("merge", None) => self.scope = Scope::Merged,
+      ("exact", None) => self.exact = true,
+      ("keywords", None) => self.exact = false,
+      ("top", None) => self.search_opts.top = None,
+      ("top", Some(n)) => self.search_opts.top = Some(n.parse()?),
+      ("offset", Some(n)) => self.search_opts.offset = n.parse()?,
+      ("sort", keys) => {
+        self.search_opts.sort = keys.map(str::parse::<ImdbSortOrder>).transpose()?;
+        self.search_opts.sort_by_year = false;
+        self.search_opts.weighted = false;
+      }
+      ("output", Some(format)) => self.search_opts.output = Some(OutputFormat::from_str(format, true)?),
+      _ => eprintln!("Unknown command `:{command}`, type `:help` for a list of commands"),
+    }
+
+    Ok(true)
+  }
+
+  /// Search for titles and display them.
+  fn search(&self, terms: &str) -> Res<()> {
+    let query = parse_search_query(terms)?;
+    let printer = create_output_printer(&self.search_opts.output(), self.general_opts);
+    let search = |kind| imdb_search_results(self.imdb, &query, self.exact, kind, &self.search_opts);
+
+    let (movies, series) = match self.scope {
+      Scope::Merged => {
+        let (results, search_terms) = search(ImdbQuery::All)?;
+        return printer.print_merged(results, self.imdb_url, search_terms.as_deref());
+      }
+      Scope::Both => (Some(search(ImdbQuery::Movies)?), Some(search(ImdbQuery::Series)?)),
+      Scope::Movies => (Some(search(ImdbQuery::Movies)?), None),
+      Scope::Series => (None, Some(search(ImdbQuery::Series)?)),
+    };
+
+    let search_terms = movies
+      .as_ref()
+      .or(series.as_ref())
+      .and_then(|(_, search_terms)| search_terms.clone());
+    printer.print(
+      movies.map(|(results, _)| results),
+      series.map(|(results, _)| results),
+      self.imdb_url,
+      search_terms.as_deref(),
+    )
+  }
+}
+
+pub fn run(
+  imdb: &Imdb,
+  imdb_url: &Url,
+  general_opts: &GeneralOpts,
+  search_opts: SearchOpts,
+  history: &Path,
+) -> Res<()> {
+  let mut shell = Shell { imdb, imdb_url, general_opts, search_opts, scope: Scope::Both, exact: false };
+  let mut editor = Editor::<()>::new();
+
+  if editor.load_history(history).is_err() {
+    debug!("No shell history found at {}", history.display());
+  }
+
+  eprintln!("Type `:help` for a list of commands, `:quit` or Ctrl-D to exit");
+
+  loop {
+    let line = match editor.readline(PROMPT) {
+      Ok(line) => line,
+      Err(ReadlineError::Interrupted) => continue,
+      Err(ReadlineError::Eof) => break,
+      Err(e) => return Err(Box::new(e)),
+    };
+
+    let line = line.trim();
+
+    if line.is_empty() {
+      continue;
+    }
+
+    editor.add_history_entry(line);
+
+    let start_time = Instant::now();
+
+    let res = match line.strip_prefix(':') {
+      Some(command) => match shell.command(command.trim()) {
+        Ok(true) => Ok(()),
+        Ok(false) => break,
+        Err(e) => Err(e),
+      },
+      None => shell.search(line),
+    };
+
+    match res {
+      Ok(()) => debug!("Shell query took {}", format_duration(Instant::now().duration_since(start_time))),
+      Err(e) => eprintln!("Error: {e}"),
+    }
+  }
+
+  if let Err(e) = editor.save_history(history) {
+    warn!("Could not save shell history to {}: {e}", history.display());
+  }
+
+  Ok(())
+}
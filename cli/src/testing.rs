@@ -0,0 +1,67 @@
+#![warn(clippy::all)]
+
+//! Fixtures shared by the tests of the subcommands.
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use reqwest::Url;
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+use tiny_http::{Response, Server};
+use tvrank::imdb::{Imdb, ImdbDatasets};
+
+const BASICS: &str = "\
+tconst\ttitleType\tprimaryTitle\toriginalTitle\tisAdult\tstartYear\tendYear\truntimeMinutes\tgenres
+tt0000001\tmovie\tAlien\tAlien\t0\t1979\t\\N\t117\tHorror,Sci-Fi
+tt0000002\tmovie\tAliens\tAliens\t0\t1986\t\\N\t137\tAction,Sci-Fi
+tt0000003\tmovie\tAlien\tAlien\t0\t2030\t\\N\t90\tHorror
+tt0000004\ttvSeries\tThe Wire\tThe Wire\t0\t2002\t2008\t59\tCrime,Drama
+tt0000009\tmovie\tThe Good, the \"Bad\" and the Ugly\tIl buono, il brutto, il cattivo\t0\t1966\t\\N\t178\tWestern
+";
+
+const RATINGS: &str = "\
+tconst\taverageRating\tnumVotes
+tt0000001\t8.5\t900000
+tt0000002\t8.4\t700000
+tt0000004\t9.3\t350000
+tt0000009\t8.8\t800000
+";
+
+/// Unblocks a server when dropped, so that its thread stops even if a test fails.
+pub struct Unblock<'a>(pub &'a Server);
+
+impl Drop for Unblock<'_> {
+  fn drop(&mut self) {
+    self.0.unblock();
+  }
+}
+
+fn gzip(data: &str) -> Vec<u8> {
+  let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+  encoder.write_all(data.as_bytes()).unwrap();
+  encoder.finish().unwrap()
+}
+
+/// Build the IMDB databases from datasets served on localhost.
+pub fn imdb(cache_dir: &Path) -> Imdb {
+  let server = Server::http("127.0.0.1:0").unwrap();
+  let url = Url::parse(&format!("http://{}/", server.server_addr())).unwrap();
+
+  thread::scope(|scope| {
+    scope.spawn(|| {
+      for request in server.incoming_requests() {
+        let data = if request.url().ends_with("title.basics.tsv.gz") {
+          BASICS
+        } else {
+          RATINGS
+        };
+        request.respond(Response::from_data(gzip(data))).unwrap();
+      }
+    });
+
+    let _unblock = Unblock(&server);
+    let datasets = ImdbDatasets::default().with_url(url).with_force_update(true);
+    Imdb::with_datasets(cache_dir, &datasets, &|_, _| {}).unwrap()
+  })
+}
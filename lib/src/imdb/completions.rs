@@ -0,0 +1,278 @@
+#![warn(clippy::all)]
+
+use crate::imdb::name_index::NameIndex;
+use fnv::FnvHashMap;
+use std::cmp::Reverse;
+use std::mem::size_of;
+
+/// Prefixes up to this length have their most popular completions precomputed.
+const SHORT_PREFIX_LEN: usize = 3;
+
+/// The number of completions that are precomputed for each short prefix.
+const SHORT_PREFIX_CAP: usize = 20;
+
+/// Keeps the most voted of the cookies pushed into it, without duplicates and without
+/// collecting all of them.
+struct MostVoted<C> {
+  /// The candidates, ranked by descending votes then ascending index. Holds at most twice
+  /// `limit` candidates before being pruned back to `limit`.
+  candidates: Vec<(Reverse<u32>, usize, C)>,
+  /// The rank of the worst candidate kept by the last pruning, which any new candidate must
+  /// beat.
+  worst: Option<(Reverse<u32>, usize)>,
+  limit: usize,
+}
+
+impl<C: Copy + Into<usize>> MostVoted<C> {
+  fn new(limit: usize) -> Self {
+    Self { candidates: Vec::with_capacity(2 * limit), worst: None, limit }
+  }
+
+  /// Add a cookie, unless it is already kept or is not voted enough to be kept.
+  ///
+  /// # Arguments
+  ///
+  /// * `cookie` - The cookie to add.
+  /// * `votes` - The number of votes of the title the cookie refers to.
+  fn push(&mut self, cookie: C, votes: u32) {
+    let rank = (Reverse(votes), cookie.into());
+
+    // A duplicate has the same rank as the original, so it is either rejected here along with
+    // it or found among the candidates.
+    if self.limit == 0 || self.worst.is_some_and(|worst| rank >= worst) {
+      return;
+    }
+
+    if self.candidates.iter().any(|&(_, index, _)| index == rank.1) {
+      return;
+    }
+
+    self.candidates.push((rank.0, rank.1, cookie));
+
+    if self.candidates.len() >= 2 * self.limit {
+      self.prune();
+    }
+  }
+
+  /// Only keep the `limit` best candidates.
+  fn prune(&mut self) {
+    if self.candidates.len() > self.limit {
+      self
+        .candidates
+        .select_nth_unstable_by_key(self.limit - 1, |&(votes, index, _)| (votes, index));
+      self.candidates.truncate(self.limit);
+    }
+
+    if self.candidates.len() == self.limit {
+      self.worst = self.candidates.iter().map(|&(votes, index, _)| (votes, index)).max();
+    }
+  }
+
+  /// The cookies kept, in descending order of votes.
+  fn into_vec(mut self) -> Vec<C> {
+    self.prune();
+    self.candidates.sort_unstable_by_key(|&(votes, index, _)| (votes, index));
+    self.candidates.into_iter().map(|(_, _, cookie)| cookie).collect()
+  }
+}
+
+/// Return the `limit` most voted cookies, in descending order of votes and without
+/// duplicates.
+///
+/// # Arguments
+///
+/// * `cookies` - The cookies to choose from.
+/// * `limit` - The maximum number of cookies to return.
+/// * `votes` - Returns the number of votes of the title a cookie refers to.
+fn most_voted<C: Copy + Into<usize>>(
+  cookies: impl Iterator<Item = C>,
+  limit: usize,
+  votes: impl Fn(C) -> u32,
+) -> Vec<C> {
+  let mut most_voted = MostVoted::new(limit);
+
+  for cookie in cookies {
+    most_voted.push(cookie, votes(cookie));
+  }
+
+  most_voted.into_vec()
+}
+
+/// Index used to complete partial title names, ranked by popularity.
+///
+/// Completions for short prefixes are precomputed, since those match too many names to be
+/// ranked quickly. Longer prefixes are looked up in the sorted names of a [`NameIndex`].
+pub(crate) struct Completions<C> {
+  /// The most voted cookies for each short prefix.
+  popular: FnvHashMap<Box<str>, Vec<C>>,
+}
+
+impl<C> Default for Completions<C> {
+  fn default() -> Self {
+    Self { popular: FnvHashMap::default() }
+  }
+}
+
+impl<C> Completions<C> {
+  /// The approximate number of bytes allocated by the index.
+  pub(crate) fn heap_size(&self) -> usize {
+    self.popular.capacity() * size_of::<(Box<str>, Vec<C>)>()
+      + self
+        .popular
+        .iter()
+        .map(|(prefix, cookies)| prefix.len() + cookies.capacity() * size_of::<C>())
+        .sum::<usize>()
+  }
+}
+
+impl<C: Copy + Into<usize>> Completions<C> {
+  /// Build the completions index.
+  ///
+  /// # Arguments
+  ///
+  /// * `names` - Index of normalized title names.
+  /// * `votes` - Returns the number of votes of the title a cookie refers to.
+  pub(crate) fn new(names: &NameIndex<C>, votes: impl Fn(C) -> u32) -> Self {
+    let mut by_prefix: FnvHashMap<&str, MostVoted<C>> = FnvHashMap::default();
+
+    for (name, postings) in names.iter() {
+      for len in 0..=SHORT_PREFIX_LEN.min(name.len()) {
+        if let Some(prefix) = name.get(..len) {
+          let most_voted = by_prefix.entry(prefix).or_insert_with(|| MostVoted::new(SHORT_PREFIX_CAP));
+
+          for &(_, cookie) in postings {
+            most_voted.push(cookie, votes(cookie));
+          }
+        }
+      }
+    }
+
+    let popular = by_prefix
+      .into_iter()
+      .map(|(prefix, most_voted)| (Box::from(prefix), most_voted.into_vec()))
+      .collect();
+
+    Self { popular }
+  }
+
+  /// Return the most voted cookies whose names start with the given prefix, in descending
+  /// order of votes.
+  ///
+  /// # Arguments
+  ///
+  /// * `names` - Index of normalized title names the completions were built from.
+  /// * `prefix` - The normalized prefix to complete.
+  /// * `limit` - The maximum number of cookies to return.
+  /// * `votes` - Returns the number of votes of the title a cookie refers to.
+  pub(crate) fn complete(
+    &self,
+    names: &NameIndex<C>,
+    prefix: &str,
+    limit: usize,
+    votes: impl Fn(C) -> u32,
+  ) -> Vec<C> {
+    if prefix.len() <= SHORT_PREFIX_LEN && limit <= SHORT_PREFIX_CAP {
+      return match self.popular.get(prefix) {
+        Some(cookies) => cookies.iter().take(limit).copied().collect(),
+        None => Vec::new(),
+      };
+    }
+
+    let cookies = names
+      .with_prefix(prefix)
+      .flat_map(|(_, postings)| postings.iter().map(|&(_, cookie)| cookie));
+
+    most_voted(cookies, limit, votes)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::completions::{most_voted, Completions};
+  use crate::imdb::name_index::NameIndex;
+  use std::time::{Duration, Instant};
+
+  const VOTES: [u32; 6] = [100, 5000, 300, 20, 70, 10];
+
+  fn make_names(names: &[(&str, usize)]) -> NameIndex<usize> {
+    NameIndex::new(names.iter().map(|&(name, cookie)| (name.to_owned(), 0, cookie)).collect())
+  }
+
+  fn make_names_and_completions() -> (NameIndex<usize>, Completions<usize>) {
+    let names: Vec<_> = ["matrix", "matrix reloaded", "mad max", "mask", "zorro", "matrix revisited"]
+      .into_iter()
+      .enumerate()
+      .map(|(cookie, name)| (name, cookie))
+      .collect();
+    let names = make_names(&names);
+    let completions = Completions::new(&names, |cookie| VOTES[cookie]);
+    (names, completions)
+  }
+
+  #[test]
+  fn test_short_prefixes() {
+    let (names, completions) = make_names_and_completions();
+    assert_eq!(completions.complete(&names, "ma", 3, |cookie| VOTES[cookie]), [1, 2, 0]);
+    assert_eq!(completions.complete(&names, "", 2, |cookie| VOTES[cookie]), [1, 2]);
+    assert_eq!(completions.complete(&names, "z", 10, |cookie| VOTES[cookie]), [4]);
+    assert!(completions.complete(&names, "x", 10, |cookie| VOTES[cookie]).is_empty());
+  }
+
+  #[test]
+  fn test_long_prefixes() {
+    let (names, completions) = make_names_and_completions();
+    assert_eq!(completions.complete(&names, "matrix", 10, |cookie| VOTES[cookie]), [1, 0, 5]);
+    assert_eq!(completions.complete(&names, "matrix re", 1, |cookie| VOTES[cookie]), [1]);
+    assert_eq!(completions.complete(&names, "ma", 100, |cookie| VOTES[cookie]), [1, 2, 0, 3, 5]);
+    assert!(completions.complete(&names, "matrices", 10, |cookie| VOTES[cookie]).is_empty());
+  }
+
+  #[test]
+  fn test_duplicates() {
+    let names = make_names(&[("amelie", 0), ("amelie poulain", 0), ("amour", 1)]);
+    let completions = Completions::new(&names, |cookie: usize| cookie as u32);
+    assert_eq!(completions.complete(&names, "am", 10, |cookie| cookie as u32), [1, 0]);
+    assert_eq!(completions.complete(&names, "amel", 10, |cookie| cookie as u32), [0]);
+  }
+
+  #[test]
+  fn test_most_voted() {
+    let votes = |cookie: usize| [5, 1, 9, 3, 9, 7, 0, 2][cookie];
+    let cookies = [0, 1, 2, 3, 4, 5, 6, 7, 2, 4, 0];
+
+    assert_eq!(most_voted(cookies.into_iter(), 3, votes), [2, 4, 5]);
+    assert_eq!(most_voted(cookies.into_iter(), 1, votes), [2]);
+    assert_eq!(most_voted(cookies.into_iter(), 100, votes), [2, 4, 5, 0, 3, 7, 1, 6]);
+    assert!(most_voted(cookies.into_iter(), 0, votes).is_empty());
+  }
+
+  #[test]
+  fn test_latency() {
+    // Names made of three syllables out of 40, so that short prefixes match many names.
+    let syllables: Vec<String> = (b'a'..=b'z')
+      .flat_map(|c| ["a", "o"].map(|v| format!("{}{v}", c as char)))
+      .take(40)
+      .collect();
+    let names: Vec<_> = (0..64_000)
+      .map(|i| (format!("{} {} {}", syllables[i % 40], syllables[i / 40 % 40], syllables[i / 1600]), 0, i))
+      .collect();
+    let names = NameIndex::new(names);
+    let votes = |cookie: usize| (cookie as u32).wrapping_mul(2_654_435_761) % 1_000_000;
+    let completions = Completions::new(&names, votes);
+
+    let time = |prefix: &str, limit| {
+      let start = Instant::now();
+
+      for _ in 0..100 {
+        assert_eq!(completions.complete(&names, prefix, limit, votes).len(), limit);
+      }
+
+      Instant::now().duration_since(start) / 100
+    };
+
+    assert!(time("", 10) < Duration::from_millis(1));
+    assert!(time("ba", 20) < Duration::from_millis(1));
+    assert!(time("ba co", 10) < Duration::from_millis(1));
+    assert!(time("ba co da", 1) < Duration::from_millis(1));
+  }
+}
@@ -1,18 +1,21 @@
 #![warn(clippy::all)]
 
+use crate::imdb::completions::Completions;
+use crate::imdb::keywords::Keywords;
+use crate::imdb::name_index::NameIndex;
 use crate::imdb::ratings::Ratings;
 use crate::imdb::title::Title;
 use crate::imdb::title::TsvAction;
 use crate::imdb::title_id::TitleId;
+use crate::utils::normalize::normalize_title;
 use crate::utils::result::Res;
-use aho_corasick::AhoCorasickBuilder;
-use aho_corasick::MatchKind as ACMatchKind;
-use derive_more::{Display, From, Into};
-use deunicode::deunicode;
+use derive_more::Display;
 use fnv::{FnvHashMap, FnvHashSet};
-use std::collections::HashMap;
+use std::cmp::Reverse;
 use std::io::{BufRead, Write};
+use std::mem::size_of;
 use std::ops::Index;
+use std::sync::OnceLock;
 
 /// Specifies the type of title a query is for. E.g. Movies or Series.
 #[derive(Clone, Copy, Display)]
@@ -25,15 +28,40 @@ pub enum Query {
   /// Query the database of Series.
   #[display(fmt = "series")]
   Series,
+
+  /// Query both the databases of Movies and Series.
+  #[display(fmt = "title")]
+  All,
 }
 
 /// A special object (i.e. a handle) that is used to refer to a movie in the database.
-#[derive(Debug, Display, PartialEq, Eq, Hash, Clone, Copy, From, Into)]
-struct MoviesCookie(usize);
+#[derive(Debug, Display, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+struct MoviesCookie(u32);
 
 /// A special object (i.e. a handle) that is used to refer to a series in the database.
-#[derive(Debug, Display, PartialEq, Eq, Hash, Clone, Copy, From, Into)]
-struct SeriesCookie(usize);
+#[derive(Debug, Display, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+struct SeriesCookie(u32);
+
+/// Implements conversions between a cookie and the index of the title it refers to. Cookies
+/// are stored as `u32`s to keep the indexes small.
+macro_rules! impl_cookie {
+  ($cookie: ident) => {
+    impl From<usize> for $cookie {
+      fn from(index: usize) -> Self {
+        Self(u32::try_from(index).expect("Too many titles in the database"))
+      }
+    }
+
+    impl From<$cookie> for usize {
+      fn from(cookie: $cookie) -> Self {
+        cookie.0 as usize
+      }
+    }
+  };
+}
+
+impl_cookie!(MoviesCookie);
+impl_cookie!(SeriesCookie);
 
 /// The primary API for access the movies and series database.
 pub struct Db {
@@ -42,15 +70,15 @@ pub struct Db {
 }
 
 impl Db {
-  /// Construct a database for movies and series, with a starting capacity for each.
+  /// Construct a database for the given movies and series and index them.
   ///
   /// # Arguments
   ///
-  /// * `movies_cap` - Starting capacity of the movies database.
-  /// * `series_cap` - Starting capacity of the series database.
-  pub fn with_capacities(movies_cap: usize, series_cap: usize) -> Self {
-    let movies = DbImpl::with_capacity(movies_cap);
-    let series = DbImpl::with_capacity(series_cap);
+  /// * `movies` - The movies to be stored.
+  /// * `series` - The series to be stored.
+  pub(crate) fn from_titles(movies: Vec<Title<'static>>, series: Vec<Title<'static>>) -> Self {
+    let movies = DbImpl::from_titles(movies);
+    let series = DbImpl::from_titles(series);
     Self { movies, series }
   }
 
@@ -69,22 +97,15 @@ impl Db {
     self.n_movies() + self.n_series()
   }
 
-  /// Insert a given title into the movies database.
-  ///
-  /// # Arguments
-  ///
-  /// * `title` - The title to be inserted.
-  pub(crate) fn store_movie(&mut self, title: Title<'static>) {
-    self.movies.store_title(title)
+  /// The number of distinct title names in the database.
+  pub fn n_names(&self) -> usize {
+    self.movies.n_names() + self.series.n_names()
   }
 
-  /// Insert the given title into the series database.
-  ///
-  /// # Arguments
-  ///
-  /// * `title` - The title to be inserted.
-  pub(crate) fn store_series(&mut self, title: Title<'static>) {
-    self.series.store_title(title)
+  /// The approximate number of bytes allocated by the database, not counting the title data
+  /// the titles refer to.
+  pub fn heap_size(&self) -> usize {
+    self.movies.heap_size() + self.series.heap_size()
   }
 
   /// Convert title data from tab separated values (TSVs) to binary.
@@ -142,11 +163,25 @@ impl Db {
   /// # Arguments
   ///
   /// * `id` - Title ID to lookup.
-  /// * `query` - Whether to query movies or series.
+  /// * `query` - Whether to query movies, series or both.
   pub(crate) fn by_id(&self, id: &TitleId, query: Query) -> Option<&Title> {
     match query {
       Query::Movies => self.movies.by_id(id),
       Query::Series => self.series.by_id(id),
+      Query::All => self.movies.by_id(id).or_else(|| self.series.by_id(id)),
+    }
+  }
+
+  /// Iterate over all titles in the database.
+  ///
+  /// # Arguments
+  ///
+  /// * `query` - Whether to query movies, series or both.
+  pub(crate) fn titles<'a>(&'a self, query: Query) -> Box<dyn Iterator<Item = &'a Title> + 'a> {
+    match query {
+      Query::Movies => Box::new(self.movies.titles()),
+      Query::Series => Box::new(self.series.titles()),
+      Query::All => Box::new(self.movies.titles().chain(self.series.titles())),
     }
   }
 
@@ -155,7 +190,7 @@ impl Db {
   /// # Arguments
   ///
   /// * `title` - The title name to search for.
-  /// * `query` - Whether to query movies or series.
+  /// * `query` - Whether to query movies, series or both.
   pub(crate) fn by_title<'a>(
     &'a self,
     title: &str,
@@ -164,6 +199,7 @@ impl Db {
     match query {
       Query::Movies => self.movies.by_title(title),
       Query::Series => self.series.by_title(title),
+      Query::All => Box::new(self.movies.by_title(title).chain(self.series.by_title(title))),
     }
   }
 
@@ -173,7 +209,7 @@ impl Db {
   ///
   /// * `title` - The title name to search for.
   /// * `year` - The year to search for titles in.
-  /// * `query` - Whether to query movies or series.
+  /// * `query` - Whether to query movies, series or both.
   pub(crate) fn by_title_and_year<'a>(
     &'a self,
     title: &str,
@@ -183,6 +219,33 @@ impl Db {
     match query {
       Query::Movies => Box::new(self.movies.by_title_and_year(title, year)),
       Query::Series => Box::new(self.series.by_title_and_year(title, year)),
+      Query::All => Box::new(
+        self
+          .movies
+          .by_title_and_year(title, year)
+          .chain(self.series.by_title_and_year(title, year)),
+      ),
+    }
+  }
+
+  /// Complete a partial title name, returning the most voted titles first.
+  ///
+  /// # Arguments
+  ///
+  /// * `prefix` - The normalized prefix of the title name.
+  /// * `limit` - The maximum number of titles to return.
+  /// * `query` - Whether to query movies, series or both.
+  pub(crate) fn complete(&self, prefix: &str, limit: usize, query: Query) -> Vec<&Title> {
+    match query {
+      Query::Movies => self.movies.complete(prefix, limit),
+      Query::Series => self.series.complete(prefix, limit),
+      Query::All => {
+        let mut titles = self.movies.complete(prefix, limit);
+        titles.extend(self.series.complete(prefix, limit));
+        titles.sort_by_key(|title| Reverse(title.votes()));
+        titles.truncate(limit);
+        titles
+      }
     }
   }
 
@@ -191,15 +254,16 @@ impl Db {
   /// # Arguments
   ///
   /// * `keywords` - Keywords to search for in title names.
-  /// * `query` - Whether to query movies or series.
-  pub(crate) fn by_keywords<'a, 'k>(
+  /// * `query` - Whether to query movies, series or both.
+  pub(crate) fn by_keywords<'a: 'k, 'k>(
     &'a self,
-    keywords: &'k [&str],
+    keywords: &'k Keywords,
     query: Query,
-  ) -> Box<dyn Iterator<Item = &'a Title> + 'a> {
+  ) -> Box<dyn Iterator<Item = &'a Title> + 'k> {
     match query {
       Query::Movies => Box::new(self.movies.by_keywords(keywords)),
       Query::Series => Box::new(self.series.by_keywords(keywords)),
+      Query::All => Box::new(self.movies.by_keywords(keywords).chain(self.series.by_keywords(keywords))),
     }
   }
 
@@ -209,31 +273,37 @@ impl Db {
   ///
   /// * `keywords` - Keywords to search for in title names.
   /// * `year` - The year to search for titles in.
-  /// * `query` - Whether to query movies or series.
-  pub(crate) fn by_keywords_and_year<'a, 'k>(
+  /// * `query` - Whether to query movies, series or both.
+  pub(crate) fn by_keywords_and_year<'a: 'k, 'k>(
     &'a self,
-    keywords: &'k [&str],
+    keywords: &'k Keywords,
     year: u16,
     query: Query,
-  ) -> Box<dyn Iterator<Item = &'a Title> + 'a> {
+  ) -> Box<dyn Iterator<Item = &'a Title> + 'k> {
     match query {
       Query::Movies => Box::new(self.movies.by_keywords_and_year(keywords, year)),
       Query::Series => Box::new(self.series.by_keywords_and_year(keywords, year)),
+      Query::All => Box::new(
+        self
+          .movies
+          .by_keywords_and_year(keywords, year)
+          .chain(self.series.by_keywords_and_year(keywords, year)),
+      ),
     }
   }
 }
 
 type ById<C> = FnvHashMap<usize, C>;
-type ByYear<C> = FnvHashMap<u16, Vec<C>>;
-type ByTitle<C> = HashMap<String, ByYear<C>>;
 
 struct DbImpl<C> {
   /// The actual storage of title information.
   titles: Vec<Title<'static>>,
   /// Map from title IDs to Titles.
   by_id: ById<C>,
-  /// Map from years to title names to Titles.
-  by_title: ByTitle<C>,
+  /// Index of title names and years to Titles.
+  by_title: NameIndex<C>,
+  /// Index of title names by prefix, built by the first completion.
+  completions: OnceLock<Completions<C>>,
 }
 
 impl<C: Into<usize>> Index<C> for DbImpl<C> {
@@ -244,72 +314,63 @@ impl<C: Into<usize>> Index<C> for DbImpl<C> {
   }
 }
 
-impl<C: From<usize>> DbImpl<C> {
-  fn next_cookie(&self) -> C {
-    C::from(self.n_titles())
-  }
-}
-
-impl<C: From<usize> + Into<usize> + Copy> DbImpl<C> {
-  /// Insert a given title into the database.
+impl<C: From<usize> + Into<usize> + Copy + Ord> DbImpl<C> {
+  /// Construct a database that stores the given titles and index them.
   ///
   /// # Arguments
   ///
-  /// * `title` - The title to be inserted.
-  fn store_title(&mut self, title: Title<'static>) {
-    let cookie = self.next_cookie();
-
-    self.insert_by_id(title.title_id(), cookie);
+  /// * `titles` - The titles to be stored.
+  fn from_titles(titles: Vec<Title<'static>>) -> Self {
+    let mut by_id = ById::with_capacity_and_hasher(titles.len(), Default::default());
+    let mut names = Vec::with_capacity(titles.len());
 
-    let lc_primary_title = title.primary_title().to_lowercase();
+    for (i, title) in titles.iter().enumerate() {
+      let cookie = C::from(i);
+      let year = title.start_year().unwrap_or(0);
 
-    let deunicoded_primary_title = deunicode(&lc_primary_title);
-    if deunicoded_primary_title != lc_primary_title {
-      self.insert_by_title_and_year(deunicoded_primary_title, title.start_year(), cookie);
-    }
+      by_id.insert(title.title_id().as_usize(), cookie);
 
-    self.insert_by_title_and_year(lc_primary_title, title.start_year(), cookie);
+      let primary_title = normalize_title(title.primary_title());
 
-    if let Some(original_title) = title.original_title() {
-      let lc_original_title = original_title.to_lowercase();
+      if let Some(original_title) = title.original_title() {
+        let original_title = normalize_title(original_title);
 
-      let deunicoded_original_title = deunicode(&lc_original_title);
-      if deunicoded_original_title != lc_original_title {
-        self.insert_by_title_and_year(deunicoded_original_title, title.start_year(), cookie);
+        if original_title != primary_title {
+          names.push((original_title, year, cookie));
+        }
       }
 
-      self.insert_by_title_and_year(lc_original_title, title.start_year(), cookie);
+      names.push((primary_title, year, cookie));
     }
 
-    self.store(title);
+    let by_title = NameIndex::new(names);
+    Self { titles, by_id, by_title, completions: Default::default() }
   }
 }
 
 impl<C> DbImpl<C> {
-  /// Construct a database with a starting capacity.
-  ///
-  /// # Arguments
-  ///
-  /// * `cap` - Starting capacity of the database.
-  fn with_capacity(cap: usize) -> Self {
-    let titles = Vec::with_capacity(cap);
-    let by_id = Default::default();
-    let by_title = Default::default();
-    Self { titles, by_id, by_title }
+  /// The number of titles stored in the database.
+  fn n_titles(&self) -> usize {
+    self.titles.len()
   }
 
-  /// Insert a title into the database.
-  ///
-  /// # Arguments
-  ///
-  /// * `title` - The title to be stored.
-  fn store(&mut self, title: Title<'static>) {
-    self.titles.push(title);
+  /// The number of distinct title names in the database.
+  fn n_names(&self) -> usize {
+    self.by_title.n_names()
   }
 
-  /// The number of titles stored in the database.
-  fn n_titles(&self) -> usize {
-    self.titles.len()
+  /// The approximate number of bytes allocated by the database, not counting the title data
+  /// the titles refer to.
+  fn heap_size(&self) -> usize {
+    self.titles.capacity() * size_of::<Title>()
+      + self.by_id.capacity() * (size_of::<(usize, C)>() + 1)
+      + self.by_title.heap_size()
+      + self.completions.get().map_or(0, Completions::heap_size)
+  }
+
+  /// Iterate over all titles stored in the database.
+  fn titles(&self) -> impl Iterator<Item = &Title> {
+    self.titles.iter()
   }
 
   /// Return a cookie for the given title ID.
@@ -328,13 +389,7 @@ impl<C> DbImpl<C> {
   /// * `title` - The title name to search for.
   /// * `year` - The year to search for titles in.
   fn cookies_by_title_and_year(&self, title: &str, year: u16) -> impl Iterator<Item = &C> {
-    if let Some(by_year) = self.by_title.get(title) {
-      if let Some(cookies) = by_year.get(&year) {
-        return cookies.iter();
-      }
-    }
-
-    [].iter()
+    self.by_title.get_by_year(title, year).iter().map(|(_, cookie)| cookie)
   }
 
   /// Search for titles by keywords.
@@ -342,18 +397,12 @@ impl<C> DbImpl<C> {
   /// # Arguments
   ///
   /// * `keywords` - Keywords to search for in title names.
-  fn cookies_by_keywords<'a, 'k>(&'a self, keywords: &'k [&str]) -> impl Iterator<Item = &'a C> {
-    let searcher = AhoCorasickBuilder::new().match_kind(ACMatchKind::LeftmostFirst).build(keywords);
-    let keywords_len = keywords.len();
+  fn cookies_by_keywords<'a: 'k, 'k>(&'a self, keywords: &'k Keywords) -> impl Iterator<Item = &'a C> + 'k {
     self
       .by_title
       .iter()
-      .filter(move |&(title, _)| {
-        let matches: FnvHashSet<_> = searcher.find_iter(title).map(|mat| mat.pattern()).collect();
-        matches.len() == keywords_len
-      })
-      .flat_map(|(_, by_year)| by_year.values())
-      .flatten()
+      .filter(move |&(title, _)| keywords.matches(title))
+      .flat_map(|(_, postings)| postings.iter().map(|(_, cookie)| cookie))
   }
 
   /// Search for titles by keywords and year.
@@ -362,47 +411,18 @@ impl<C> DbImpl<C> {
   ///
   /// * `keywords` - Keywords to search for in title names.
   /// * `year` - The year to search for titles in.
-  fn cookies_by_keywords_and_year<'a, 'k>(
+  fn cookies_by_keywords_and_year<'a: 'k, 'k>(
     &'a self,
-    keywords: &'k [&str],
+    keywords: &'k Keywords,
     year: u16,
-  ) -> impl Iterator<Item = &'a C> {
-    let searcher = AhoCorasickBuilder::new().match_kind(ACMatchKind::LeftmostFirst).build(keywords);
-    let keywords_len = keywords.len();
+  ) -> impl Iterator<Item = &'a C> + 'k {
     self
       .by_title
       .iter()
-      .filter(move |&(title, _)| {
-        let matches: FnvHashSet<_> = searcher.find_iter(title).map(|mat| mat.pattern()).collect();
-        matches.len() == keywords_len
+      .filter(move |&(title, _)| keywords.matches(title))
+      .flat_map(move |(_, postings)| {
+        postings.iter().filter(move |&&(y, _)| y == year).map(|(_, cookie)| cookie)
       })
-      .filter_map(move |(_, by_year)| by_year.get(&year))
-      .flatten()
-  }
-
-  /// Insert a cookie with the given title ID.
-  ///
-  /// # Arguments
-  ///
-  /// * `id` - Title ID to insert the cookie for.
-  /// * `cookie` - Cookie to be inserted.
-  fn insert_by_id(&mut self, id: &TitleId, cookie: C) -> bool {
-    self.by_id.insert(id.as_usize(), cookie).is_none()
-  }
-
-  /// Insert cookie for the title with the given name and year.
-  ///
-  /// # Arguments
-  ///
-  /// * `title` - Name of the title to be inserted.
-  /// * `year` - Release year of the title to be inserted.
-  /// * `cookie` - Cookie to be inserted.
-  fn insert_by_title_and_year(&mut self, title: String, year: Option<u16>, cookie: C) {
-    if let Some(year) = year {
-      self.by_title.entry(title).or_default().entry(year).or_default().push(cookie);
-    } else {
-      self.by_title.entry(title).or_default().entry(0).or_default().push(cookie);
-    }
   }
 }
 
@@ -416,17 +436,29 @@ impl<C: Into<usize> + Copy> DbImpl<C> {
     self.cookie_by_id(id).map(|&cookie| &self[cookie])
   }
 
+  /// Complete a partial title name, returning the most voted titles first.
+  ///
+  /// The completions index is built by the first call, so that loading the database does not
+  /// pay for it when completions are never needed.
+  ///
+  /// # Arguments
+  ///
+  /// * `prefix` - The normalized prefix of the title name.
+  /// * `limit` - The maximum number of titles to return.
+  pub(crate) fn complete(&self, prefix: &str, limit: usize) -> Vec<&Title> {
+    let votes = |cookie: C| self[cookie].votes();
+    let completions = self.completions.get_or_init(|| Completions::new(&self.by_title, votes));
+    let cookies = completions.complete(&self.by_title, prefix, limit, votes);
+    cookies.into_iter().map(|cookie| &self[cookie]).collect()
+  }
+
   /// Find titles by name.
   ///
   /// # Arguments
   ///
   /// * `title` - Title name to search for.
   pub(crate) fn by_title<'a>(&'a self, title: &str) -> Box<dyn Iterator<Item = &Title> + 'a> {
-    if let Some(by_year) = self.by_title.get(title) {
-      return Box::new(by_year.values().flatten().map(|&cookie| &self[cookie]));
-    }
-
-    Box::new(std::iter::empty())
+    Box::new(self.by_title.get(title).iter().map(|&(_, cookie)| &self[cookie]))
   }
 
   /// Find titles by name and year.
@@ -439,27 +471,40 @@ impl<C: Into<usize> + Copy> DbImpl<C> {
     self.cookies_by_title_and_year(title, year).map(|&cookie| &self[cookie])
   }
 
-  /// Search for titles by keywords.
+  /// Search for titles by keywords. Titles whose primary and original names both match are
+  /// only returned once.
   ///
   /// # Arguments
   ///
   /// * `keywords` - Keywords to search for.
-  pub(crate) fn by_keywords<'a, 'k>(&'a self, keywords: &'k [&str]) -> impl Iterator<Item = &'a Title> {
-    self.cookies_by_keywords(keywords).map(|&cookie| &self[cookie])
+  pub(crate) fn by_keywords<'a: 'k, 'k>(
+    &'a self,
+    keywords: &'k Keywords,
+  ) -> impl Iterator<Item = &'a Title> + 'k {
+    let mut seen = FnvHashSet::<usize>::default();
+    self
+      .cookies_by_keywords(keywords)
+      .filter(move |&&cookie| seen.insert(cookie.into()))
+      .map(|&cookie| &self[cookie])
   }
 
-  /// Search for titles by keywords and year.
+  /// Search for titles by keywords and year. Titles whose primary and original names both
+  /// match are only returned once.
   ///
   /// # Arguments
   ///
   /// * `keywords` - Keywords to search for.
   /// * `year` - The year to search for titles in.
-  pub(crate) fn by_keywords_and_year<'a, 'k>(
+  pub(crate) fn by_keywords_and_year<'a: 'k, 'k>(
     &'a self,
-    keywords: &'k [&str],
+    keywords: &'k Keywords,
     year: u16,
-  ) -> impl Iterator<Item = &'a Title> {
-    self.cookies_by_keywords_and_year(keywords, year).map(|&cookie| &self[cookie])
+  ) -> impl Iterator<Item = &'a Title> + 'k {
+    let mut seen = FnvHashSet::<usize>::default();
+    self
+      .cookies_by_keywords_and_year(keywords, year)
+      .filter(move |&&cookie| seen.insert(cookie.into()))
+      .map(|&cookie| &self[cookie])
   }
 }
 
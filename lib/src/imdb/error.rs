@@ -54,6 +54,18 @@ pub enum Err {
   /// Thrown if a problem occurs while parsing a title
   #[display(fmt = "Error parsing title: {}", _0)]
   ParsingTitle(String),
+  /// Thrown if a keyword search does not contain any keywords
+  #[display(fmt = "Empty set of keywords")]
+  NoKeywords,
+  /// Thrown if a keyword search contains too many keywords
+  #[display(fmt = "Too many keywords")]
+  TooManyKeywords,
+  /// Thrown if titles cannot be sorted by the given key
+  #[display(fmt = "Unknown sort key `{}`", _0)]
+  SortKey(String),
+  /// Thrown if the given sort direction is not `asc` or `desc`
+  #[display(fmt = "Unknown sort direction `{}`", _0)]
+  SortDirection(String),
 }
 
 impl Err {
@@ -82,6 +94,16 @@ impl Err {
     Err(Box::new(Err::Eof))
   }
 
+  /// Returns a Result containing a NoKeywords error
+  pub(crate) fn no_keywords<T>() -> Res<T> {
+    Err(Box::new(Err::NoKeywords))
+  }
+
+  /// Returns a Result containing a TooManyKeywords error
+  pub(crate) fn too_many_keywords<T>() -> Res<T> {
+    Err(Box::new(Err::TooManyKeywords))
+  }
+
   /// Returns a Result containing an UnsupportedTitleType error with the given TitleType
   pub(crate) fn unsupported_title_type<T>(title_type: TitleType) -> Res<T> {
     Err(Box::new(Err::UnsupportedTitleType(title_type)))
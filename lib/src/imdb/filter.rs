@@ -0,0 +1,242 @@
+#![warn(clippy::all)]
+
+use crate::imdb::genre::{Genre, Genres};
+use crate::imdb::title::Title;
+use crate::imdb::title_type::TitleType;
+use std::ops::{Bound, RangeBounds};
+
+/// A pair of bounds, as produced by any of the standard range types.
+type Bounds<T> = (Bound<T>, Bound<T>);
+
+/// Convert any range (e.g. `1980..1990`, `70..` or `..=110`) into a pair of bounds.
+///
+/// # Arguments
+///
+/// * `range` - The range to convert.
+fn bounds<T: Copy>(range: impl RangeBounds<T>) -> Bounds<T> {
+  (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+/// Whether a pair of bounds does not restrict anything.
+///
+/// # Arguments
+///
+/// * `bounds` - The bounds to check.
+fn is_unbounded<T>(bounds: &Bounds<T>) -> bool {
+  matches!(bounds, (Bound::Unbounded, Bound::Unbounded))
+}
+
+/// Check an optional value against a pair of bounds.
+///
+/// Unknown values only pass when the bounds do not restrict anything.
+///
+/// # Arguments
+///
+/// * `bounds` - The bounds to check against.
+/// * `value` - The value to check, if known.
+fn contains<T: PartialOrd>(bounds: &Bounds<T>, value: Option<T>) -> bool {
+  match value {
+    Some(value) => bounds.contains(&value),
+    None => is_unbounded(bounds),
+  }
+}
+
+/// Criteria that titles need to satisfy, used to narrow down titles when browsing.
+///
+/// All criteria need to be satisfied for a title to match. By default, a filter matches
+/// every title.
+#[derive(Debug, Clone)]
+pub struct Filter {
+  genres: Genres,
+  title_types: Vec<TitleType>,
+  years: Bounds<u16>,
+  rating: Bounds<u8>,
+  votes: Bounds<u32>,
+  runtime: Bounds<u16>,
+}
+
+impl Default for Filter {
+  fn default() -> Self {
+    Self {
+      genres: Genres::default(),
+      title_types: Vec::new(),
+      years: bounds(..),
+      rating: bounds(..),
+      votes: bounds(..),
+      runtime: bounds(..),
+    }
+  }
+}
+
+impl Filter {
+  /// Only match titles that are associated with the given genre.
+  ///
+  /// Can be called multiple times, in which case titles need to be associated with all
+  /// the given genres.
+  ///
+  /// # Arguments
+  ///
+  /// * `genre` - The genre titles need to be associated with.
+  pub fn with_genre(mut self, genre: Genre) -> Self {
+    self.genres.add(genre);
+    self
+  }
+
+  /// Only match titles of the given type.
+  ///
+  /// Can be called multiple times, in which case titles need to be of any of the given
+  /// types.
+  ///
+  /// # Arguments
+  ///
+  /// * `title_type` - The type titles need to be of.
+  pub fn with_title_type(mut self, title_type: TitleType) -> Self {
+    if !self.title_types.contains(&title_type) {
+      self.title_types.push(title_type);
+    }
+
+    self
+  }
+
+  /// Only match titles released in the given range of years (e.g. `1980..1990`).
+  ///
+  /// # Arguments
+  ///
+  /// * `years` - The range of release years.
+  pub fn with_years(mut self, years: impl RangeBounds<u16>) -> Self {
+    self.years = bounds(years);
+    self
+  }
+
+  /// Only match titles with a rating in the given range (e.g. `70..`).
+  ///
+  /// Ratings are out of 100.
+  ///
+  /// # Arguments
+  ///
+  /// * `rating` - The range of ratings.
+  pub fn with_rating(mut self, rating: impl RangeBounds<u8>) -> Self {
+    self.rating = bounds(rating);
+    self
+  }
+
+  /// Only match titles with a number of votes in the given range (e.g. `50_000..`).
+  ///
+  /// Titles without a rating are considered to have zero votes.
+  ///
+  /// # Arguments
+  ///
+  /// * `votes` - The range of number of votes.
+  pub fn with_votes(mut self, votes: impl RangeBounds<u32>) -> Self {
+    self.votes = bounds(votes);
+    self
+  }
+
+  /// Only match titles with a runtime in the given range of minutes (e.g. `..=110`).
+  ///
+  /// # Arguments
+  ///
+  /// * `runtime` - The range of runtimes in minutes.
+  pub fn with_runtime(mut self, runtime: impl RangeBounds<u16>) -> Self {
+    self.runtime = bounds(runtime);
+    self
+  }
+
+  /// Whether the given title satisfies all the criteria of the filter.
+  ///
+  /// # Arguments
+  ///
+  /// * `title` - The title to check.
+  pub fn matches(&self, title: &Title) -> bool {
+    if !title.genres().contains_all(self.genres) {
+      return false;
+    }
+
+    if !self.title_types.is_empty() && !self.title_types.contains(&title.title_type()) {
+      return false;
+    }
+
+    let rating = title.rating();
+
+    contains(&self.years, title.start_year())
+      && contains(&self.rating, rating.map(|rating| rating.rating()))
+      && self.votes.contains(&rating.map_or(0, |rating| rating.votes()))
+      && contains(&self.runtime, title.runtime().map(|runtime| (runtime.as_secs() / 60) as u16))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::filter::Filter;
+  use crate::imdb::genre::Genre;
+  use crate::imdb::ratings::{Rating, Ratings};
+  use crate::imdb::title::Title;
+  use crate::imdb::title_type::TitleType;
+
+  fn make_title(ratings: &Ratings) -> Title<'static> {
+    let title = Title::from_tsv(
+      b"tt0000003\tmovie\tPauvre Pierrot\tPauvre Pierrot\t0\t1892\t\\N\t4\tAnimation,Comedy,Romance",
+      ratings,
+    )
+    .unwrap();
+    let title: Option<Title> = title.into();
+    title.unwrap()
+  }
+
+  #[test]
+  fn test_default() {
+    let title = make_title(&Ratings::default());
+    assert!(Filter::default().matches(&title));
+  }
+
+  #[test]
+  fn test_genres() {
+    let title = make_title(&Ratings::default());
+    assert!(Filter::default().with_genre(Genre::Comedy).matches(&title));
+    assert!(Filter::default()
+      .with_genre(Genre::Comedy)
+      .with_genre(Genre::Romance)
+      .matches(&title));
+    assert!(!Filter::default()
+      .with_genre(Genre::Comedy)
+      .with_genre(Genre::SciFi)
+      .matches(&title));
+  }
+
+  #[test]
+  fn test_title_types() {
+    let title = make_title(&Ratings::default());
+    assert!(Filter::default().with_title_type(TitleType::Movie).matches(&title));
+    assert!(!Filter::default().with_title_type(TitleType::Short).matches(&title));
+    assert!(Filter::default()
+      .with_title_type(TitleType::Short)
+      .with_title_type(TitleType::Movie)
+      .matches(&title));
+  }
+
+  #[test]
+  fn test_years_and_runtime() {
+    let title = make_title(&Ratings::default());
+    assert!(Filter::default().with_years(1890..1900).matches(&title));
+    assert!(Filter::default().with_years(..=1892).matches(&title));
+    assert!(!Filter::default().with_years(1893..).matches(&title));
+    assert!(Filter::default().with_runtime(..=4).matches(&title));
+    assert!(!Filter::default().with_runtime(5..).matches(&title));
+  }
+
+  #[test]
+  fn test_rating_and_votes() {
+    let unrated = make_title(&Ratings::default());
+    assert!(!Filter::default().with_rating(60..).matches(&unrated));
+    assert!(Filter::default().with_votes(..10).matches(&unrated));
+    assert!(!Filter::default().with_votes(1..).matches(&unrated));
+
+    let mut ratings = Ratings::default();
+    ratings.insert(3, Rating::new(65, 1603));
+    let rated = make_title(&ratings);
+    assert!(Filter::default().with_rating(60..).matches(&rated));
+    assert!(!Filter::default().with_rating(..65).matches(&rated));
+    assert!(Filter::default().with_votes(1000..).matches(&rated));
+    assert!(!Filter::default().with_votes(50_000..).matches(&rated));
+  }
+}
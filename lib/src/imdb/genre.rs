@@ -1,5 +1,6 @@
 #![warn(clippy::all)]
 
+use crate::utils::tokens::names_match;
 use derive_more::Display;
 use enum_utils::FromStr;
 use serde::ser::SerializeSeq;
@@ -95,6 +96,18 @@ impl Genre {
   pub(crate) const unsafe fn from(value: u8) -> Self {
     std::mem::transmute(value)
   }
+
+  /// Returns an iterator over all the Genre items
+  pub fn all() -> impl Iterator<Item = Self> {
+    (0..=Self::max()).map(|value| unsafe { Self::from(value) })
+  }
+
+  /// Looks up a Genre by name, ignoring case and punctuation (e.g. "sci-fi" or "SciFi")
+  /// # Arguments
+  /// * `name` - Name of the genre
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::all().find(|genre| names_match(name, &genre.to_string()))
+  }
 }
 
 /// Represents the set of genres a title is associated with
@@ -110,6 +123,37 @@ impl Genres {
     self.0 |= 1 << index;
   }
 
+  /// Returns true if all the genres in `other` are also in the Genres
+  /// # Arguments
+  /// * `other` - Genres to check for
+  pub fn contains_all(&self, other: Genres) -> bool {
+    self.0 & other.0 == other.0
+  }
+
+  /// Returns true if the Genres is empty
+  pub fn is_empty(&self) -> bool {
+    self.0 == 0
+  }
+
+  /// Returns the number of genres in the Genres
+  pub fn len(&self) -> usize {
+    self.0.count_ones() as usize
+  }
+
+  /// Returns the genres that are in both the Genres and `other`
+  /// # Arguments
+  /// * `other` - Genres to intersect with
+  pub fn intersection(&self, other: Genres) -> Genres {
+    Genres(self.0 & other.0)
+  }
+
+  /// Returns the genres that are in either the Genres or `other`
+  /// # Arguments
+  /// * `other` - Genres to unite with
+  pub fn union(&self, other: Genres) -> Genres {
+    Genres(self.0 | other.0)
+  }
+
   /// Returns an iterator for the genres
   pub fn iter(&self) -> GenresIter {
     GenresIter::new(*self)
@@ -313,6 +357,47 @@ mod tests {
     assert_eq!(genres.get(27), None);
   }
 
+  #[test]
+  fn test_genre_from_name() {
+    assert_eq!(Genre::from_name("Sci-Fi"), Some(Genre::SciFi));
+    assert_eq!(Genre::from_name("sci-fi"), Some(Genre::SciFi));
+    assert_eq!(Genre::from_name("scifi"), Some(Genre::SciFi));
+    assert_eq!(Genre::from_name("COMEDY"), Some(Genre::Comedy));
+    assert_eq!(Genre::from_name("film noir"), Some(Genre::FilmNoir));
+    assert_eq!(Genre::from_name("cartoon"), None);
+    assert_eq!(Genre::all().count(), usize::from(Genre::max()) + 1);
+  }
+
+  #[test]
+  fn test_genres_contains_all() {
+    let genres = make_genres();
+
+    let mut other = Genres::default();
+    assert!(other.is_empty());
+    assert!(genres.contains_all(other));
+
+    other.add(Genre::Music);
+    other.add(Genre::War);
+    assert!(genres.contains_all(other));
+
+    other.add(Genre::Drama);
+    assert!(!genres.contains_all(other));
+  }
+
+  #[test]
+  fn test_genres_set_operations() {
+    let genres = make_genres();
+    assert_eq!(genres.len(), 3);
+
+    let mut other = Genres::default();
+    other.add(Genre::Music);
+    other.add(Genre::Drama);
+
+    assert_eq!(genres.intersection(other).iter().collect::<Vec<_>>(), [Genre::Music]);
+    assert_eq!(genres.union(other).len(), 4);
+    assert!(genres.intersection(Genres::default()).is_empty());
+  }
+
   #[test]
   fn test_genres_iter() {
     let genres = make_genres();
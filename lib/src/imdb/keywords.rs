@@ -0,0 +1,339 @@
+#![warn(clippy::all)]
+
+use crate::imdb::error::Err;
+use crate::utils::normalize::{is_article, normalize_title, normalize_words};
+use crate::utils::result::Res;
+use aho_corasick::AhoCorasick;
+use std::fmt;
+
+/// The maximum number of distinct terms a keyword search can contain.
+const MAX_TERMS: usize = u128::BITS as usize;
+
+/// A single term of a keyword search, before it is compiled.
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+  /// A word or a quoted phrase, which may be excluded (e.g. `-lego`).
+  Term { text: String, is_phrase: bool, is_excluded: bool },
+  /// The `OR` operator.
+  Or,
+}
+
+/// Split a keyword search into its tokens, normalizing each term.
+///
+/// # Arguments
+///
+/// * `input` - The keyword search, e.g. `"star wars" -lego batman OR superman`.
+fn tokenize(input: &str) -> Vec<Token> {
+  let mut tokens = Vec::new();
+  let mut chars = input.chars().peekable();
+
+  loop {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+    let mut is_excluded = false;
+    if chars.next_if_eq(&'-').is_some() {
+      is_excluded = true;
+    }
+
+    let (text, is_phrase) = match chars.peek() {
+      None => break,
+      Some('"') => {
+        chars.next();
+        let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+        (phrase.split_whitespace().collect::<Vec<_>>().join(" "), true)
+      }
+      Some(_) => {
+        let mut word = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+          word.push(c);
+        }
+        (word, false)
+      }
+    };
+
+    if text == "OR" && !is_phrase && !is_excluded {
+      tokens.push(Token::Or);
+      continue;
+    }
+
+    let text = if is_phrase {
+      normalize_title(&text)
+    } else {
+      normalize_words(&text)
+    };
+
+    if !text.is_empty() {
+      tokens.push(Token::Term { text, is_phrase, is_excluded });
+    }
+  }
+
+  tokens
+}
+
+/// A keyword search over title names.
+///
+/// A keyword search is made of words and quoted phrases, all of which need to appear in a
+/// title name for it to match. Words and phrases can be excluded by prefixing them with `-`,
+/// and alternatives can be given using `OR`. For example, `"star wars" -lego` matches all
+/// titles containing the phrase "star wars" but not the word "lego", and `batman OR superman
+/// returns` matches titles containing either "batman" or "superman" as well as "returns".
+///
+/// Terms are normalized the same way title names are, so matching is case-insensitive and
+/// ignores punctuation (e.g. "spider-man" matches "Spider Man").
+#[derive(Debug, Clone)]
+pub struct Keywords {
+  /// The distinct words and phrases of the search.
+  terms: Vec<String>,
+  /// Which of the terms are phrases.
+  phrases: u128,
+  /// Each clause is a set of alternative terms, at least one of which needs to match.
+  clauses: Vec<u128>,
+  /// The set of terms none of which must match.
+  excluded: u128,
+  /// The automaton used to find all the terms at once.
+  searcher: AhoCorasick,
+}
+
+impl Keywords {
+  /// Parse a keyword search.
+  ///
+  /// Single-letter words and articles are ignored, unless they are the only term of the
+  /// search, since articles are dropped from the start of title names when they are indexed.
+  ///
+  /// # Arguments
+  ///
+  /// * `input` - The keyword search, e.g. `"star wars" -lego batman OR superman`.
+  ///
+  /// # Errors
+  ///
+  /// * `Err::NoKeywords` - If the search does not contain any (non-excluded) terms.
+  /// * `Err::TooManyKeywords` - If the search contains too many distinct terms.
+  pub fn parse(input: &str) -> Res<Self> {
+    let mut tokens = tokenize(input);
+
+    let n_included = tokens
+      .iter()
+      .filter(|token| matches!(token, Token::Term { is_excluded: false, .. }))
+      .count();
+    if n_included > 1 {
+      tokens.retain(|token| match token {
+        Token::Term { text, is_phrase: false, is_excluded: false } => {
+          (text.chars().nth(1).is_some() || text.chars().all(|c| c.is_ascii_digit())) && !is_article(text)
+        }
+        _ => true,
+      });
+    }
+
+    let mut terms: Vec<String> = Vec::new();
+    let mut phrases = 0;
+    let mut clauses: Vec<u128> = Vec::new();
+    let mut excluded = 0;
+    let mut pending_or = false;
+
+    for token in tokens {
+      let (text, is_phrase, is_excluded) = match token {
+        Token::Or => {
+          pending_or = !clauses.is_empty();
+          continue;
+        }
+        Token::Term { text, is_phrase, is_excluded } => (text, is_phrase, is_excluded),
+      };
+
+      let index = match terms.iter().position(|term| *term == text) {
+        Some(index) => index,
+        None if terms.len() == MAX_TERMS => return Err::too_many_keywords(),
+        None => {
+          terms.push(text);
+          terms.len() - 1
+        }
+      };
+
+      let bit = 1 << index;
+
+      if is_phrase {
+        phrases |= bit;
+      }
+
+      if is_excluded {
+        excluded |= bit;
+      } else if let (true, Some(clause)) = (pending_or, clauses.last_mut()) {
+        *clause |= bit;
+      } else {
+        clauses.push(bit);
+      }
+
+      pending_or = false;
+    }
+
+    if clauses.is_empty() {
+      return Err::no_keywords();
+    }
+
+    let searcher = AhoCorasick::new(&terms);
+    Ok(Self { terms, phrases, clauses, excluded, searcher })
+  }
+
+  /// Whether the given normalized title name matches the keyword search.
+  ///
+  /// # Arguments
+  ///
+  /// * `title` - The title name to check.
+  pub fn matches(&self, title: &str) -> bool {
+    let mut found: u128 = 0;
+
+    for mat in self.searcher.find_overlapping_iter(title) {
+      found |= 1 << mat.pattern();
+
+      if found & self.excluded != 0 {
+        return false;
+      }
+    }
+
+    self.clauses.iter().all(|&clause| found & clause != 0)
+  }
+
+  /// Write a term, quoting it if it is a phrase.
+  fn fmt_term(&self, f: &mut fmt::Formatter, index: usize) -> fmt::Result {
+    if self.phrases & (1 << index) != 0 {
+      write!(f, "\"{}\"", self.terms[index])
+    } else {
+      write!(f, "{}", self.terms[index])
+    }
+  }
+}
+
+impl fmt::Display for Keywords {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let indices = |set: u128| (0..self.terms.len()).filter(move |&index| set & (1 << index) != 0);
+
+    for (i, &clause) in self.clauses.iter().enumerate() {
+      if i > 0 {
+        write!(f, ", ")?;
+      }
+
+      for (j, index) in indices(clause).enumerate() {
+        if j > 0 {
+          write!(f, " OR ")?;
+        }
+
+        self.fmt_term(f, index)?;
+      }
+    }
+
+    for index in indices(self.excluded) {
+      write!(f, ", -")?;
+      self.fmt_term(f, index)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::keywords::{tokenize, Keywords, Token};
+
+  fn term(text: &str, is_phrase: bool, is_excluded: bool) -> Token {
+    Token::Term { text: text.to_owned(), is_phrase, is_excluded }
+  }
+
+  #[test]
+  fn test_tokenize() {
+    assert_eq!(
+      tokenize(r#"  "Star   Wars" -lego -"the clone" Batman OR superman "unterminated"#),
+      [
+        term("star wars", true, false),
+        term("lego", false, true),
+        term("clone", true, true),
+        term("batman", false, false),
+        Token::Or,
+        term("superman", false, false),
+        term("unterminated", true, false),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_words() {
+    let keywords = Keywords::parse("the great gatsby").unwrap();
+    assert!(keywords.matches("the great gatsby"));
+    assert!(keywords.matches("gatsby the great"));
+    assert!(!keywords.matches("the great escape"));
+    assert_eq!(keywords.to_string(), "great, gatsby");
+  }
+
+  #[test]
+  fn test_short_words() {
+    let keywords = Keywords::parse("a b").unwrap_err();
+    assert_eq!(keywords.to_string(), "Empty set of keywords");
+
+    let keywords = Keywords::parse("x").unwrap();
+    assert!(keywords.matches("malcolm x"));
+    assert!(!keywords.matches("10 things i hate about you"));
+
+    let keywords = Keywords::parse("malcolm x").unwrap();
+    assert!(keywords.matches("malcolm in the middle"));
+
+    let keywords = Keywords::parse("alien 3").unwrap();
+    assert!(keywords.matches("alien 3"));
+    assert!(!keywords.matches("aliens"));
+  }
+
+  #[test]
+  fn test_phrases() {
+    let keywords = Keywords::parse(r#""star wars""#).unwrap();
+    assert!(keywords.matches("star wars: episode iv - a new hope"));
+    assert!(!keywords.matches("wars of the star"));
+    assert_eq!(keywords.to_string(), r#""star wars""#);
+  }
+
+  #[test]
+  fn test_exclusions() {
+    let keywords = Keywords::parse(r#""star wars" -lego"#).unwrap();
+    assert!(keywords.matches("star wars"));
+    assert!(!keywords.matches("lego star wars: the freemaker adventures"));
+    assert_eq!(keywords.to_string(), r#""star wars", -lego"#);
+
+    assert!(Keywords::parse("-lego").is_err());
+  }
+
+  #[test]
+  fn test_alternatives() {
+    let keywords = Keywords::parse("batman OR superman returns").unwrap();
+    assert!(keywords.matches("batman returns"));
+    assert!(keywords.matches("superman returns"));
+    assert!(!keywords.matches("batman begins"));
+    assert!(!keywords.matches("the return of the jedi"));
+    assert_eq!(keywords.to_string(), "batman OR superman, returns");
+
+    let keywords = Keywords::parse("OR batman or superman").unwrap();
+    assert!(!keywords.matches("batman"));
+    assert!(keywords.matches("batman or superman"));
+  }
+
+  #[test]
+  fn test_normalization() {
+    let keywords = Keywords::parse(r#""The Great Gatsby""#).unwrap();
+    assert!(keywords.matches("great gatsby"));
+
+    let keywords = Keywords::parse("Rocky II").unwrap();
+    assert!(keywords.matches("rocky 2"));
+    assert_eq!(keywords.to_string(), "rocky, 2");
+
+    let keywords = Keywords::parse(r#""mission: impossible" -"ghost protocol""#).unwrap();
+    assert!(keywords.matches("mission impossible fallout"));
+    assert!(!keywords.matches("mission impossible ghost protocol"));
+
+    assert!(Keywords::parse("- & -").is_ok());
+    assert!(Keywords::parse("- :").is_err());
+  }
+
+  #[test]
+  fn test_overlapping() {
+    let keywords = Keywords::parse("spider man").unwrap();
+    assert!(keywords.matches("spider-man"));
+
+    let keywords = Keywords::parse("spiderman spider").unwrap();
+    assert!(keywords.matches("spiderman"));
+  }
+}
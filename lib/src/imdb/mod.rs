@@ -3,11 +3,18 @@
 
 //! Module for TVrank to use the IMDB dataset (TSV dumps) as a source.
 
+mod completions;
 mod db;
 mod error;
+mod filter;
 mod genre;
+mod keywords;
+mod name_index;
 mod ratings;
+mod results;
 mod service;
+mod similarity;
+mod sort;
 mod title;
 mod title_header;
 mod title_id;
@@ -16,8 +23,13 @@ mod tokens;
 
 pub use db::Query as ImdbQuery;
 pub use error::Err as ImdbErr;
+pub use filter::Filter as ImdbFilter;
 pub use genre::{Genre as ImdbGenre, Genres as ImdbGenres};
-pub use service::Service as Imdb;
+pub use keywords::Keywords as ImdbKeywords;
+pub use ratings::{Rating as ImdbRating, RatingPrior as ImdbRatingPrior};
+pub use results::Results as ImdbResults;
+pub use service::{Datasets as ImdbDatasets, Service as Imdb};
+pub use sort::{SortDirection as ImdbSortDirection, SortKey as ImdbSortKey, SortOrder as ImdbSortOrder};
 pub use title::Title as ImdbTitle;
 pub use title_id::TitleId as ImdbTitleId;
-pub use title_type::TitleType as ImdbTitleType;
+pub use title_type::{TitleKind as ImdbTitleKind, TitleType as ImdbTitleType};
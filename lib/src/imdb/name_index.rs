@@ -0,0 +1,246 @@
+#![warn(clippy::all)]
+
+use std::mem::size_of;
+
+/// Convert a length to an offset into one of the arrays of a [`NameIndex`].
+///
+/// # Arguments
+///
+/// * `len` - The length of the array.
+fn offset(len: usize) -> u32 {
+  u32::try_from(len).expect("Name index is too large")
+}
+
+/// Index of titles by normalized name and release year, stored in a few flat arrays.
+///
+/// Distinct names are interned, in sorted order, into a single string arena. Each name refers
+/// to a range of one shared posting list of `(year, cookie)` pairs, sorted by year. Titles
+/// without a release year are stored under year `0`.
+pub(crate) struct NameIndex<C> {
+  /// All the distinct names, concatenated in sorted order.
+  arena: String,
+  /// The offset in the arena where each name ends. Name `i` starts where name `i - 1` ends.
+  name_ends: Vec<u32>,
+  /// The offset in the postings where the postings of each name end.
+  posting_ends: Vec<u32>,
+  /// The years and cookies of the titles, grouped by name and sorted by year.
+  postings: Vec<(u16, C)>,
+}
+
+impl<C> Default for NameIndex<C> {
+  fn default() -> Self {
+    Self { arena: String::new(), name_ends: Vec::new(), posting_ends: Vec::new(), postings: Vec::new() }
+  }
+}
+
+impl<C: Copy + Ord> NameIndex<C> {
+  /// Build the index.
+  ///
+  /// # Arguments
+  ///
+  /// * `entries` - Normalized title names, along with the release year and cookie of the title.
+  pub(crate) fn new(mut entries: Vec<(String, u16, C)>) -> Self {
+    entries.sort_unstable();
+    entries.dedup();
+
+    let mut index = Self::default();
+    index.postings.reserve_exact(entries.len());
+
+    for (name, year, cookie) in entries {
+      let is_new_name = match index.n_names() {
+        0 => true,
+        n => index.name(n - 1) != name,
+      };
+
+      if is_new_name {
+        if index.n_names() > 0 {
+          index.posting_ends.push(offset(index.postings.len()));
+        }
+
+        index.arena.push_str(&name);
+        index.name_ends.push(offset(index.arena.len()));
+      }
+
+      index.postings.push((year, cookie));
+    }
+
+    if index.n_names() > 0 {
+      index.posting_ends.push(offset(index.postings.len()));
+    }
+
+    index.arena.shrink_to_fit();
+    index.name_ends.shrink_to_fit();
+    index.posting_ends.shrink_to_fit();
+    index
+  }
+}
+
+impl<C> NameIndex<C> {
+  /// The number of distinct names in the index.
+  pub(crate) fn n_names(&self) -> usize {
+    self.name_ends.len()
+  }
+
+  /// The number of bytes allocated by the index.
+  pub(crate) fn heap_size(&self) -> usize {
+    self.arena.capacity()
+      + (self.name_ends.capacity() + self.posting_ends.capacity()) * size_of::<u32>()
+      + self.postings.capacity() * size_of::<(u16, C)>()
+  }
+
+  /// Return the postings of titles with the given name.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The normalized name to look up.
+  pub(crate) fn get(&self, name: &str) -> &[(u16, C)] {
+    let i = self.partition_point(|candidate| candidate < name);
+
+    if i < self.n_names() && self.name(i) == name {
+      self.postings(i)
+    } else {
+      &[]
+    }
+  }
+
+  /// Return the postings of titles with the given name and release year.
+  ///
+  /// # Arguments
+  ///
+  /// * `name` - The normalized name to look up.
+  /// * `year` - The release year to look up.
+  pub(crate) fn get_by_year(&self, name: &str, year: u16) -> &[(u16, C)] {
+    let postings = self.get(name);
+    let start = postings.partition_point(|&(candidate, _)| candidate < year);
+    let end = postings.partition_point(|&(candidate, _)| candidate <= year);
+    &postings[start..end]
+  }
+
+  /// Iterate over all names, along with the postings of each.
+  pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &[(u16, C)])> {
+    (0..self.n_names()).map(|i| (self.name(i), self.postings(i)))
+  }
+
+  /// Iterate over the names that start with the given prefix, in sorted order, along with the
+  /// postings of each.
+  ///
+  /// # Arguments
+  ///
+  /// * `prefix` - The normalized prefix of the names.
+  pub(crate) fn with_prefix<'a>(
+    &'a self,
+    prefix: &'a str,
+  ) -> impl Iterator<Item = (&'a str, &'a [(u16, C)])> {
+    let start = self.partition_point(|candidate| candidate < prefix);
+
+    (start..self.n_names())
+      .map(|i| (self.name(i), self.postings(i)))
+      .take_while(move |(name, _)| name.starts_with(prefix))
+  }
+
+  /// The name at the given position.
+  ///
+  /// # Arguments
+  ///
+  /// * `i` - The position of the name, in sorted order.
+  fn name(&self, i: usize) -> &str {
+    let start = if i == 0 {
+      0
+    } else {
+      self.name_ends[i - 1] as usize
+    };
+    &self.arena[start..self.name_ends[i] as usize]
+  }
+
+  /// The postings of the name at the given position.
+  ///
+  /// # Arguments
+  ///
+  /// * `i` - The position of the name, in sorted order.
+  fn postings(&self, i: usize) -> &[(u16, C)] {
+    let start = if i == 0 {
+      0
+    } else {
+      self.posting_ends[i - 1] as usize
+    };
+    &self.postings[start..self.posting_ends[i] as usize]
+  }
+
+  /// Return the position of the first name for which the predicate is false, assuming it is
+  /// true for all the names before it.
+  ///
+  /// # Arguments
+  ///
+  /// * `pred` - The predicate to partition names by.
+  fn partition_point(&self, pred: impl Fn(&str) -> bool) -> usize {
+    let (mut low, mut high) = (0, self.n_names());
+
+    while low < high {
+      let mid = low + (high - low) / 2;
+
+      if pred(self.name(mid)) {
+        low = mid + 1;
+      } else {
+        high = mid;
+      }
+    }
+
+    low
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::name_index::NameIndex;
+
+  fn make_index() -> NameIndex<usize> {
+    let entries = [
+      ("matrix", 1999, 0),
+      ("matrix reloaded", 2003, 1),
+      ("mad max", 1979, 2),
+      ("mad max", 2015, 3),
+      ("matrix", 1999, 0),
+      ("mad max", 0, 4),
+      ("zorro", 1998, 5),
+    ];
+
+    NameIndex::new(
+      entries
+        .iter()
+        .map(|&(name, year, cookie)| (name.to_owned(), year, cookie))
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn test_get() {
+    let index = make_index();
+    assert_eq!(index.n_names(), 4);
+    assert_eq!(index.get("mad max"), [(0, 4), (1979, 2), (2015, 3)]);
+    assert_eq!(index.get("matrix"), [(1999, 0)]);
+    assert_eq!(index.get("zorro"), [(1998, 5)]);
+    assert!(index.get("mad").is_empty());
+    assert!(index.get("zzz").is_empty());
+    assert!(NameIndex::<usize>::default().get("matrix").is_empty());
+  }
+
+  #[test]
+  fn test_get_by_year() {
+    let index = make_index();
+    assert_eq!(index.get_by_year("mad max", 2015), [(2015, 3)]);
+    assert_eq!(index.get_by_year("mad max", 0), [(0, 4)]);
+    assert!(index.get_by_year("mad max", 2000).is_empty());
+  }
+
+  #[test]
+  fn test_names() {
+    let index = make_index();
+    let names: Vec<_> = index.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["mad max", "matrix", "matrix reloaded", "zorro"]);
+
+    let names: Vec<_> = index.with_prefix("mat").map(|(name, _)| name).collect();
+    assert_eq!(names, ["matrix", "matrix reloaded"]);
+    assert_eq!(index.with_prefix("").count(), 4);
+    assert_eq!(index.with_prefix("x").count(), 0);
+  }
+}
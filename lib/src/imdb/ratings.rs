@@ -24,12 +24,7 @@ pub struct Rating {
 
 impl PartialOrd for Rating {
   fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-    match self.rating.partial_cmp(&other.rating) {
-      Some(Ordering::Equal) => {}
-      ord => return ord,
-    }
-
-    self.votes.partial_cmp(&other.votes)
+    Some(self.cmp(other))
   }
 }
 
@@ -40,7 +35,7 @@ impl Ord for Rating {
       ord => return ord,
     }
 
-    self.rating.cmp(&other.rating)
+    self.votes.cmp(&other.votes)
   }
 }
 
@@ -71,6 +66,56 @@ impl Rating {
   pub fn votes(&self) -> u32 {
     self.votes
   }
+
+  /// Returns the average user rating weighted by the number of votes, so that titles with
+  /// few votes are pulled towards the prior rating
+  /// # Arguments
+  /// * `prior` - Prior rating and number of votes
+  pub fn weighted(&self, prior: &RatingPrior) -> f32 {
+    let votes = self.votes as f32;
+    let prior_votes = prior.votes as f32;
+
+    if votes + prior_votes == 0.0 {
+      return f32::from(self.rating);
+    }
+
+    (votes * f32::from(self.rating) + prior_votes * f32::from(prior.rating)) / (votes + prior_votes)
+  }
+}
+
+/// Prior used to compute weighted ratings, following the formula of IMDB's Top 250 list:
+/// `(v * R + m * C) / (v + m)`, where `R` and `v` are the rating and number of votes of a
+/// title, `C` is the prior rating and `m` is the prior number of votes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RatingPrior {
+  rating: u8,
+  votes: u32,
+}
+
+impl Default for RatingPrior {
+  fn default() -> Self {
+    Self::new(70, 25_000)
+  }
+}
+
+impl RatingPrior {
+  /// Create a new RatingPrior
+  /// # Arguments
+  /// * `rating` - Rating (out of 100) that titles with few votes are pulled towards
+  /// * `votes` - Number of votes after which a title's own rating starts to dominate
+  pub fn new(rating: u8, votes: u32) -> Self {
+    Self { rating, votes }
+  }
+
+  /// Returns the prior rating
+  pub fn rating(&self) -> u8 {
+    self.rating
+  }
+
+  /// Returns the prior number of votes
+  pub fn votes(&self) -> u32 {
+    self.votes
+  }
 }
 
 /// Maps a set of title IDs to their corresponding ratings
@@ -158,6 +203,7 @@ impl Ratings {
 #[cfg(test)]
 mod tests_ratings {
   use crate::imdb::ratings::Rating;
+  use crate::imdb::ratings::RatingPrior;
   use crate::imdb::ratings::Ratings;
   use crate::imdb::title_id::TitleId;
   use indoc::indoc;
@@ -195,4 +241,21 @@ mod tests_ratings {
     let id = TitleId::try_from("tt0000011".as_bytes()).unwrap();
     assert_eq!(ratings.get(&id.as_usize()), None);
   }
+
+  #[test]
+  fn test_rating_ord() {
+    assert!(Rating::new(70, 10) > Rating::new(69, 1000));
+    assert!(Rating::new(70, 1000) > Rating::new(70, 10));
+    assert_eq!(Rating::new(70, 10).cmp(&Rating::new(70, 10)), std::cmp::Ordering::Equal);
+  }
+
+  #[test]
+  fn test_weighted_rating() {
+    let prior = RatingPrior::new(70, 1000);
+    assert_eq!(Rating::new(89, 0).weighted(&prior), 70.0);
+    assert_eq!(Rating::new(90, 1000).weighted(&prior), 80.0);
+    assert!(Rating::new(89, 12).weighted(&prior) < Rating::new(87, 900_000).weighted(&prior));
+    assert_eq!(Rating::new(89, 12).weighted(&RatingPrior::new(70, 0)), 89.0);
+    assert_eq!(Rating::new(89, 0).weighted(&RatingPrior::new(70, 0)), 89.0);
+  }
 }
@@ -0,0 +1,272 @@
+#![warn(clippy::all)]
+
+use crate::imdb::filter::Filter;
+use crate::imdb::sort::SortOrder;
+use crate::imdb::title::Title;
+
+/// A set of titles found by one or more queries, that can be sorted and paginated.
+///
+/// Titles are sorted lazily, the first time a page of them is requested. A result set can
+/// also be filled with only the best ranked titles of a query, in which case the titles that
+/// were left out still count towards its total length.
+pub struct Results<'a, 'storage> {
+  titles: Vec<&'a Title<'storage>>,
+  n_omitted: usize,
+  order: SortOrder,
+  offset: usize,
+  limit: Option<usize>,
+  is_sorted: bool,
+}
+
+impl<'a, 'storage> IntoIterator for Results<'a, 'storage> {
+  type Item = &'a Title<'storage>;
+
+  type IntoIter = std::vec::IntoIter<Self::Item>;
+
+  /// Iterate over all the titles, regardless of the page.
+  fn into_iter(self) -> Self::IntoIter {
+    self.titles.into_iter()
+  }
+}
+
+impl<'a, 'storage> Extend<&'a Title<'storage>> for Results<'a, 'storage> {
+  fn extend<T: IntoIterator<Item = &'a Title<'storage>>>(&mut self, iter: T) {
+    self.titles.extend(iter);
+    self.is_sorted = false;
+  }
+}
+
+impl<'a, 'storage> Results<'a, 'storage> {
+  /// Create an empty result set, with a single page containing all titles.
+  ///
+  /// # Arguments
+  ///
+  /// * `order` - The order to sort titles in.
+  pub fn new(order: SortOrder) -> Self {
+    Self { titles: Vec::new(), n_omitted: 0, order, offset: 0, limit: None, is_sorted: true }
+  }
+
+  /// Skip the given number of titles at the start of the page.
+  ///
+  /// # Arguments
+  ///
+  /// * `offset` - The number of titles to skip.
+  pub fn with_offset(mut self, offset: usize) -> Self {
+    self.offset = offset;
+    self
+  }
+
+  /// Limit the page to the given number of titles.
+  ///
+  /// # Arguments
+  ///
+  /// * `limit` - The maximum number of titles in the page.
+  pub fn with_limit(mut self, limit: usize) -> Self {
+    self.limit = Some(limit);
+    self
+  }
+
+  /// The order titles are sorted in.
+  pub fn order(&self) -> &SortOrder {
+    &self.order
+  }
+
+  /// The number of titles skipped at the start of the page.
+  pub fn offset(&self) -> usize {
+    self.offset
+  }
+
+  /// The maximum number of titles in the page, if any.
+  pub fn limit(&self) -> Option<usize> {
+    self.limit
+  }
+
+  /// Add a title to the result set.
+  ///
+  /// # Arguments
+  ///
+  /// * `title` - The title to add.
+  pub fn push(&mut self, title: &'a Title<'storage>) {
+    self.titles.push(title);
+    self.is_sorted = false;
+  }
+
+  /// Add the titles that satisfy a filter to the result set.
+  ///
+  /// # Arguments
+  ///
+  /// * `titles` - The titles to add.
+  /// * `filter` - The criteria the titles need to satisfy.
+  pub fn extend_filtered(&mut self, titles: impl IntoIterator<Item = &'a Title<'storage>>, filter: &Filter) {
+    self.extend(titles.into_iter().filter(|title| filter.matches(title)))
+  }
+
+  /// Add the best ranked titles of a query to the result set.
+  ///
+  /// # Arguments
+  ///
+  /// * `top` - The best ranked titles and the total number of titles matching the query, as
+  ///   returned by e.g. [`crate::imdb::Imdb::top`].
+  pub fn extend_top(&mut self, (titles, total): (Vec<&'a Title<'storage>>, usize)) {
+    self.n_omitted += total.saturating_sub(titles.len());
+    self.extend(titles);
+  }
+
+  /// Add titles that are already ranked by some other criteria, e.g. by similarity, keeping
+  /// them in the given order after the titles already in the result set.
+  ///
+  /// # Arguments
+  ///
+  /// * `titles` - The ranked titles to add.
+  pub fn extend_ranked(&mut self, titles: impl IntoIterator<Item = &'a Title<'storage>>) {
+    self.sort();
+    self.titles.extend(titles);
+  }
+
+  /// The total number of titles in the result set, regardless of the page, including the
+  /// titles left out by [`Results::extend_top`].
+  pub fn total_len(&self) -> usize {
+    self.titles.len() + self.n_omitted
+  }
+
+  /// Whether the result set does not contain any titles.
+  pub fn is_empty(&self) -> bool {
+    self.total_len() == 0
+  }
+
+  /// The number of titles in the page.
+  pub fn len(&self) -> usize {
+    let len = self.titles.len().saturating_sub(self.offset);
+
+    match self.limit {
+      Some(limit) => limit.min(len),
+      None => len,
+    }
+  }
+
+  /// Whether some titles of the result set are not in the page.
+  pub fn is_truncated(&self) -> bool {
+    self.len() < self.total_len()
+  }
+
+  /// Iterate over all the titles, regardless of the page, in no particular order.
+  pub fn iter(&self) -> impl Iterator<Item = &'a Title<'storage>> + '_ {
+    self.titles.iter().copied()
+  }
+
+  /// The titles in the page, sorted.
+  pub fn page(&mut self) -> &[&'a Title<'storage>] {
+    self.sort();
+    let start = self.offset.min(self.titles.len());
+    &self.titles[start..start + self.len()]
+  }
+
+  /// Consume the result set, returning the titles in the page, sorted.
+  pub fn into_page(mut self) -> Vec<&'a Title<'storage>> {
+    self.page().to_vec()
+  }
+
+  /// Sort the titles, unless they are already sorted.
+  fn sort(&mut self) {
+    if !self.is_sorted {
+      let order = &self.order;
+      self.titles.sort_unstable_by(|a, b| order.compare(a, b));
+      self.is_sorted = true;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::filter::Filter;
+  use crate::imdb::ratings::{Rating, Ratings};
+  use crate::imdb::results::Results;
+  use crate::imdb::sort::SortOrder;
+  use crate::imdb::title::Title;
+
+  fn make_titles(ratings: &Ratings) -> Vec<Title<'static>> {
+    let lines: [&'static [u8]; 4] = [
+      b"tt0000001\tmovie\tFirst\tFirst\t0\t1990\t\\N\t90\tDrama",
+      b"tt0000002\tmovie\tSecond\tSecond\t0\t1991\t\\N\t90\tDrama",
+      b"tt0000003\tmovie\tThird\tThird\t0\t1992\t\\N\t90\tDrama",
+      b"tt0000004\tmovie\tFourth\tFourth\t0\t1993\t\\N\t90\tDrama",
+    ];
+
+    lines
+      .iter()
+      .map(|line| Option::<Title>::from(Title::from_tsv(line, ratings).unwrap()).unwrap())
+      .collect()
+  }
+
+  fn names<'a>(results: &'a mut Results) -> Vec<&'a str> {
+    results.page().iter().map(|title| title.primary_title()).collect()
+  }
+
+  #[test]
+  fn test_pages() {
+    let mut ratings = Ratings::default();
+    for (id, rating) in [(1, 60), (2, 80), (3, 70), (4, 50)] {
+      ratings.insert(id, Rating::new(rating, 100));
+    }
+
+    let titles = make_titles(&ratings);
+
+    let mut results = Results::new(SortOrder::default());
+    results.extend(titles.iter());
+    assert_eq!(names(&mut results), ["Second", "Third", "First", "Fourth"]);
+    assert!(!results.is_truncated());
+
+    let mut results = Results::new(SortOrder::default()).with_offset(1).with_limit(2);
+    results.extend(titles.iter());
+    assert_eq!(names(&mut results), ["Third", "First"]);
+    assert_eq!(results.iter().count(), 4);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results.total_len(), 4);
+    assert!(results.is_truncated());
+
+    let mut results = Results::new(SortOrder::default()).with_offset(3).with_limit(2);
+    results.extend(titles.iter());
+    assert_eq!(names(&mut results), ["Fourth"]);
+
+    let mut results = Results::new(SortOrder::default()).with_offset(10);
+    results.extend(titles.iter());
+    assert!(names(&mut results).is_empty());
+    assert!(results.is_truncated());
+  }
+
+  #[test]
+  fn test_filtered() {
+    let titles = make_titles(&Ratings::default());
+
+    let mut results = Results::new(SortOrder::default());
+    results.extend_filtered(titles.iter(), &Filter::default().with_years(1992..));
+    results.push(&titles[0]);
+    assert_eq!(names(&mut results), ["Fourth", "Third", "First"]);
+  }
+
+  #[test]
+  fn test_ranked() {
+    let titles = make_titles(&Ratings::default());
+
+    let mut results = Results::new(SortOrder::default()).with_limit(3);
+    results.extend([&titles[0], &titles[2]]);
+    results.extend_ranked([&titles[1], &titles[3]]);
+    assert_eq!(names(&mut results), ["Third", "First", "Second"]);
+  }
+
+  #[test]
+  fn test_top() {
+    let titles = make_titles(&Ratings::default());
+
+    let mut results = Results::new(SortOrder::default()).with_offset(1).with_limit(2);
+    results.extend_top((vec![&titles[3], &titles[2], &titles[1]], 4));
+    assert_eq!(names(&mut results), ["Third", "Second"]);
+    assert_eq!(results.total_len(), 4);
+    assert!(results.is_truncated());
+
+    let mut results = Results::new(SortOrder::default()).with_offset(1).with_limit(2);
+    results.extend_top((vec![&titles[3]], 4));
+    assert!(names(&mut results).is_empty());
+    assert!(!results.is_empty());
+  }
+}
@@ -1,32 +1,131 @@
 #![warn(clippy::all)]
 
 use crate::imdb::db::{Db, Query};
+use crate::imdb::filter::Filter;
+use crate::imdb::keywords::Keywords;
+use crate::imdb::ratings::RatingPrior;
+use crate::imdb::similarity::{most_similar, top_similar};
+use crate::imdb::sort::SortOrder;
 use crate::imdb::title::Title;
 use crate::imdb::title_id::TitleId;
+use crate::imdb::title_type::TitleKind;
 use crate::utils::io::Progress;
+use crate::utils::normalize::normalize_title;
+use crate::utils::random::Random;
 use crate::utils::result::Res;
+use crate::utils::top::top_n_by;
 use flate2::bufread::GzDecoder;
-use fnv::FnvHashSet;
 use humantime::format_duration;
 use log::{debug, log_enabled};
-use parking_lot::{const_mutex, Mutex};
 use rayon::prelude::*;
 use reqwest::blocking::{Client, Response};
 use reqwest::Url;
+use std::cmp::Reverse;
 use std::fs::{self, File};
 use std::io::{self, BufReader, BufWriter};
 use std::path::Path;
 use std::time::{Duration, Instant, SystemTime};
 
+/// Returns the best `n` titles in descending order of rank, without collecting all of them,
+/// along with the number of titles they were chosen from
+/// # Arguments
+/// * `titles` - Titles to choose from
+/// * `order` - Order to rank titles by
+/// * `n` - Maximum number of titles to return
+fn top_n<'a, 'storage>(
+  titles: impl Iterator<Item = &'a Title<'storage>>,
+  order: &SortOrder,
+  n: usize,
+) -> (Vec<&'a Title<'storage>>, usize) {
+  // Titles that come first in the sort order are the better ranked ones.
+  top_n_by(titles, n, |a, b| order.compare(b, a))
+}
+
+/// Returns the given number of bytes in mebibytes, for display
+/// # Arguments
+/// * `bytes` - Number of bytes
+fn mebibytes(bytes: usize) -> f64 {
+  bytes as f64 / (1024.0 * 1024.0)
+}
+
 /// Struct providing the movies and series databases and the related services
+///
+/// Titles are spread across a fixed number of databases (shards) by ID, so that looking up a
+/// title by ID only needs a single database, while other queries search the shards in parallel.
 pub struct Service {
   dbs: Vec<Db>,
 }
 
+/// Number of databases titles are spread across
+const N_SHARDS: usize = 16;
+
 const IMDB: &str = "https://datasets.imdbws.com/";
 const RATINGS_FILENAME: &str = "title.ratings.tsv.gz";
 const BASICS_FILENAME: &str = "title.basics.tsv.gz";
 
+/// Age after which the databases are rebuilt by default, about a month
+const MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+/// Where the IMDB datasets are downloaded from, and when the databases built from them are
+/// rebuilt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datasets {
+  url: Option<Url>,
+  max_age: Duration,
+  force_update: bool,
+}
+
+impl Default for Datasets {
+  fn default() -> Self {
+    Self { url: None, max_age: MAX_AGE, force_update: false }
+  }
+}
+
+impl Datasets {
+  /// Download the datasets from the given URL, e.g. a mirror, instead of the IMDB one
+  /// # Arguments
+  /// * `url` - URL of the directory holding the dataset files
+  pub fn with_url(mut self, mut url: Url) -> Self {
+    // Dataset files are joined to the URL, which would otherwise replace its last segment.
+    if !url.path().ends_with('/') {
+      let path = format!("{}/", url.path());
+      url.set_path(&path);
+    }
+
+    self.url = Some(url);
+    self
+  }
+
+  /// Rebuild the databases once they are older than the given age, instead of a month
+  /// # Arguments
+  /// * `max_age` - Age after which the databases are rebuilt
+  pub fn with_max_age(mut self, max_age: Duration) -> Self {
+    self.max_age = max_age;
+    self
+  }
+
+  /// Rebuild the databases regardless of their age
+  /// # Arguments
+  /// * `force_update` - True if the databases should be rebuilt regardless of their age
+  pub fn with_force_update(mut self, force_update: bool) -> Self {
+    self.force_update = force_update;
+    self
+  }
+
+  /// URL of the directory holding the dataset files
+  pub fn url(&self) -> Res<Url> {
+    match &self.url {
+      Some(url) => Ok(url.clone()),
+      None => Ok(Url::parse(IMDB)?),
+    }
+  }
+
+  /// Age after which the databases are rebuilt
+  pub fn max_age(&self) -> Duration {
+    self.max_age
+  }
+}
+
 impl Service {
   /// Returns a Service struct holding movies/series databases
   /// # Arguments
@@ -34,17 +133,23 @@ impl Service {
   /// * `force_db_update` - True if the databases should be updated regardless of their age
   /// * `progress_fn` - Function that keeps track of the download progress
   pub fn new(cache_dir: &Path, force_db_update: bool, progress_fn: &dyn Fn(Option<u64>, u64)) -> Res<Self> {
-    // Delete old imdb cache directory.
-    let old_cache_dir = cache_dir.join("imdb");
-    let _ = fs::remove_dir_all(old_cache_dir);
-
-    // Delete old imdb cache file.
-    let old_cache_file = cache_dir.join("imdb.tvrankdb");
-    let _ = fs::remove_file(old_cache_file);
+    Self::remove_legacy_cache(cache_dir);
+    Self::with_datasets(cache_dir, &Datasets::default().with_force_update(force_db_update), progress_fn)
+  }
 
+  /// Returns a Service struct holding movies/series databases, built from the given datasets
+  /// # Arguments
+  /// * `cache_dir` - Directory path of the database files
+  /// * `datasets` - Where the datasets are downloaded from and when the databases are rebuilt
+  /// * `progress_fn` - Function that keeps track of the download progress
+  pub fn with_datasets(
+    cache_dir: &Path,
+    datasets: &Datasets,
+    progress_fn: &dyn Fn(Option<u64>, u64),
+  ) -> Res<Self> {
     let movies_db_filename = cache_dir.join("imdb-movies.tvrankdb");
     let series_db_filename = cache_dir.join("imdb-series.tvrankdb");
-    Self::ensure_db_files(&movies_db_filename, &series_db_filename, force_db_update, progress_fn)?;
+    Self::ensure_db_files(&movies_db_filename, &series_db_filename, datasets, progress_fn)?;
 
     let start = Instant::now();
     let movies_data = fs::read(movies_db_filename)?;
@@ -61,98 +166,102 @@ impl Service {
       let mut total_movies = 0;
       let mut total_series = 0;
       let mut total_entries = 0;
+      let mut total_names = 0;
+      let mut total_size = 0;
 
       for (i, db) in service.dbs.iter().enumerate() {
         let movies = db.n_movies();
         let series = db.n_series();
         let entries = db.n_entries();
+        let names = db.n_names();
+        let size = db.heap_size();
 
         total_movies += movies;
         total_series += series;
         total_entries += entries;
-
-        debug!("IMDB database (thread {i}) contains {movies} movies and {series} series ({entries} entries)");
+        total_names += names;
+        total_size += size;
+
+        debug!(
+          "IMDB database (shard {i}) contains {movies} movies and {series} series ({entries} entries, \
+           {names} names) in {:.1} MiB",
+          mebibytes(size)
+        );
       }
 
       debug!(
-        "IMDB database contains {total_movies} movies and {total_series} series ({total_entries} entries)"
+        "IMDB database contains {total_movies} movies and {total_series} series ({total_entries} entries, \
+         {total_names} names)"
+      );
+      debug!(
+        "IMDB database uses {:.1} MiB for title data and {:.1} MiB for indexes",
+        mebibytes(movies_data.len() + series_data.len()),
+        mebibytes(total_size)
       );
     }
 
     Ok(service)
   }
 
-  /// Parses titles from the given binary and pushes them into the given vector of titles and the movies/series databases
+  /// Deletes the databases left behind by older versions in the given cache directory
+  ///
+  /// Only call this on a directory owned by TVrank, as it deletes its `imdb` subdirectory.
   /// # Arguments
-  /// * `cursor` - Cursor at the binary to read the titles from
-  /// * `titles` - Vector to store the titles temporarily before writing to the database
-  /// * `db` - Database to store movies or series
-  fn titles_from_binary<const IS_MOVIE: bool>(
-    cursor: &Mutex<&mut &'static [u8]>,
-    titles: &mut Vec<Title<'static>>,
-    db: &mut Db,
-  ) {
-    loop {
-      let mut cursor = cursor.lock();
-
-      if (*cursor).is_empty() {
-        break;
-      }
-
-      for _ in 0..100 {
-        if (*cursor).is_empty() {
-          break;
-        }
-
-        let title = match Title::from_binary(&mut cursor) {
-          Ok(title) => title,
-          Err(e) => panic!("Error parsing title: {}", e),
-        };
-
-        titles.push(title);
-      }
+  /// * `cache_dir` - Directory path of the database files
+  pub fn remove_legacy_cache(cache_dir: &Path) {
+    // Delete old imdb cache directory.
+    let old_cache_dir = cache_dir.join("imdb");
+    let _ = fs::remove_dir_all(old_cache_dir);
 
-      drop(cursor);
+    // Delete old imdb cache file.
+    let old_cache_file = cache_dir.join("imdb.tvrankdb");
+    let _ = fs::remove_file(old_cache_file);
+  }
 
-      for &title in titles.iter() {
-        if IS_MOVIE {
-          db.store_movie(title);
-        } else {
-          db.store_series(title);
-        }
-      }
+  /// Parses titles from the given binary and spreads them across shards by ID
+  /// # Arguments
+  /// * `data` - Binary movies or series data
+  /// * `cap` - Expected number of titles in the binary
+  fn titles_from_binary(mut data: &'static [u8], cap: usize) -> Vec<Vec<Title<'static>>> {
+    let mut shards: Vec<Vec<Title>> = (0..N_SHARDS).map(|_| Vec::with_capacity(cap / N_SHARDS)).collect();
+
+    while !data.is_empty() {
+      let title = match Title::from_binary(&mut data) {
+        Ok(title) => title,
+        Err(e) => panic!("Error parsing title: {}", e),
+      };
 
-      titles.clear();
+      shards[Self::shard_of(title.title_id())].push(title);
     }
+
+    shards
   }
 
-  /// Parses titles from the given binary and inserts them into the movies/series databases
+  /// Parses titles from the given binary and indexes them into one movies/series database per
+  /// shard, in parallel
   /// # Arguments
   /// * `movies_data` - Binary movies data
   /// * `series_data` - Binary series data
-  fn from_binary(mut movies_data: &'static [u8], mut series_data: &'static [u8]) -> Self {
-    let nthreads = rayon::current_num_threads();
-    let dbs = const_mutex(Vec::with_capacity(nthreads));
-    let movies_cursor: Mutex<&mut &'static [u8]> = const_mutex(&mut movies_data);
-    let series_cursor: Mutex<&mut &'static [u8]> = const_mutex(&mut series_data);
-
-    rayon::scope(|scope| {
-      for _ in 0..nthreads {
-        let dbs = &dbs;
-        let movies_cursor = &movies_cursor;
-        let series_cursor = &series_cursor;
-
-        scope.spawn(move |_| {
-          let mut db = Db::with_capacities(1_900_000 / nthreads, 270_000 / nthreads);
-          let mut titles = Vec::with_capacity(100);
-          Self::titles_from_binary::<true>(movies_cursor, &mut titles, &mut db);
-          Self::titles_from_binary::<false>(series_cursor, &mut titles, &mut db);
-          dbs.lock().push(db);
-        });
-      }
-    });
+  fn from_binary(movies_data: &'static [u8], series_data: &'static [u8]) -> Self {
+    let (movies, series) = rayon::join(
+      || Self::titles_from_binary(movies_data, 1_900_000),
+      || Self::titles_from_binary(series_data, 270_000),
+    );
+
+    let dbs = movies
+      .into_par_iter()
+      .zip(series)
+      .map(|(movies, series)| Db::from_titles(movies, series))
+      .collect();
+
+    Self { dbs }
+  }
 
-    Self { dbs: dbs.into_inner() }
+  /// Returns the index of the shard storing the title with the given ID
+  /// # Arguments
+  /// * `id` - ID of the title
+  fn shard_of(id: &TitleId) -> usize {
+    id.as_usize() % N_SHARDS
   }
 
   /// Returns the file at the given path if it exists, or an Ok Result if it is not found.
@@ -169,13 +278,13 @@ impl Service {
     }
   }
 
-  /// Determines if the given database needs to be updated. Returns true if the force_db_update parameter is true or if the database
-  /// have not been updated for longer than one month.
+  /// Determines if the given database needs to be updated. Returns true if the force_update option of the datasets is set or if
+  /// the database has not been updated for longer than their max_age.
   /// # Arguments
   /// * `file` - Database file to be checked
-  /// * `force_db_update` - True if the database should be updated regardless of its age
-  fn file_needs_update(file: &Option<File>, force_db_update: bool) -> Res<bool> {
-    if force_db_update {
+  /// * `datasets` - Specifies when the database should be updated
+  fn file_needs_update(file: &Option<File>, datasets: &Datasets) -> Res<bool> {
+    if datasets.force_update {
       Ok(true)
     } else if let Some(f) = file {
       let md = f.metadata()?;
@@ -185,8 +294,7 @@ impl Service {
         Err(_) => return Ok(true),
       };
 
-      // Older than a month.
-      Ok(age >= Duration::from_secs(60 * 60 * 24 * 30))
+      Ok(age >= datasets.max_age)
     } else {
       // The file does not exist.
       Ok(true)
@@ -220,33 +328,37 @@ impl Service {
   }
 
   /// Ensures that the movies and series databases exist and are up-to-date. The databases are created if they don't exist, and updated if they
-  /// are outdated or if the force_db_update parameter is set to true.
+  /// are outdated or if the force_update option of the datasets is set.
   /// # Arguments
   /// * `movies_db_filename` - Path to the movies database
   /// * `series_db_filename` - Path to the series database
-  /// * `force_db_update` - True if the databases should be updated regardless of their age
+  /// * `datasets` - Where the datasets are downloaded from and when the databases are rebuilt
   /// * `progress_fn` - Function that keeps track of the download progress
   fn ensure_db_files(
     movies_db_filename: &Path,
     series_db_filename: &Path,
-    force_db_update: bool,
+    datasets: &Datasets,
     progress_fn: &dyn Fn(Option<u64>, u64),
   ) -> Res<()> {
     let needs_update = {
       let movies_db_file = Self::file_exists(movies_db_filename)?;
       let series_db_file = Self::file_exists(series_db_filename)?;
-      Self::file_needs_update(&movies_db_file, force_db_update)?
-        || Self::file_needs_update(&series_db_file, force_db_update)?
+      Self::file_needs_update(&movies_db_file, datasets)?
+        || Self::file_needs_update(&series_db_file, datasets)?
     };
 
     if needs_update {
-      if force_db_update {
+      if datasets.force_update {
         debug!("Force-update is enabled, IMDB database is going to be re-fetched and built");
       } else {
-        debug!("IMDB database does not exist or is more than a month old, going to fetch and build");
+        debug!(
+          "IMDB database does not exist or is more than {} old, going to fetch and build",
+          format_duration(datasets.max_age)
+        );
       }
 
-      let imdb_url = Url::parse(IMDB)?;
+      let imdb_url = datasets.url()?;
+      debug!("Fetching IMDB datasets from {imdb_url}");
 
       let basics_resp = Self::get_response(&imdb_url, BASICS_FILENAME)?;
       let ratings_resp = Self::get_response(&imdb_url, RATINGS_FILENAME)?;
@@ -269,7 +381,7 @@ impl Service {
 
       Db::to_binary(ratings_downloader, basics_downloader, movies_db_writer, series_db_writer)?;
     } else {
-      debug!("IMDB database exists and is less than a month old");
+      debug!("IMDB database exists and is less than {} old", format_duration(datasets.max_age));
     }
 
     Ok(())
@@ -278,79 +390,378 @@ impl Service {
   /// Query titles by ID
   /// # Arguments
   /// * `id` - ID of the title to be queried
-  /// * `query` - Specifies if movies or series are queried
+  /// * `query` - Specifies if movies, series or both are queried
   pub fn by_id(&self, id: &TitleId, query: Query) -> Option<&Title> {
-    let res = self
-      .dbs
-      .par_iter()
-      .map(|db| db.by_id(id, query))
-      .filter(|res| res.is_some())
-      .flatten()
-      .collect::<Vec<_>>();
+    self.dbs[Self::shard_of(id)].by_id(id, query)
+  }
 
-    if res.is_empty() {
-      None
-    } else {
-      Some(unsafe { res.get_unchecked(0) })
+  /// Select the best `n` titles of every database in parallel, then the best `n` among those
+  /// # Arguments
+  /// * `order` - Order to rank titles by
+  /// * `n` - Maximum number of titles to return
+  /// * `titles` - Returns the titles to choose from in a database
+  fn top_of<'a, I>(
+    &'a self,
+    order: &SortOrder,
+    n: usize,
+    titles: impl Fn(&'a Db) -> I + Sync + Send,
+  ) -> (Vec<&'a Title<'a>>, usize)
+  where
+    I: Iterator<Item = &'a Title<'a>>,
+  {
+    let tops = self.dbs.par_iter().map(|db| top_n(titles(db), order, n)).collect::<Vec<_>>();
+    let total = tops.iter().map(|(_, total)| total).sum();
+    let (top, _) = top_n(tops.into_iter().flat_map(|(top, _)| top), order, n);
+    (top, total)
+  }
+
+  /// Query the best ranked titles matching a filter, without needing any search terms
+  ///
+  /// Returns at most `n` titles and the total number of titles matching the filter.
+  /// # Arguments
+  /// * `filter` - Criteria the titles must satisfy
+  /// * `order` - Order to rank titles by
+  /// * `query` - Specifies if movies, series or both are queried
+  /// * `n` - Maximum number of titles to return
+  pub fn top(&self, filter: &Filter, order: &SortOrder, query: Query, n: usize) -> (Vec<&Title>, usize) {
+    self.top_of(order, n, |db| db.titles(query).filter(|title| filter.matches(title)))
+  }
+
+  /// Query the best ranked titles by title and optionally year, without collecting all of them
+  ///
+  /// Returns at most `n` titles and the total number of titles matching the title and filter.
+  /// # Arguments
+  /// * `title` - Title to be queried, normalized as in [`Service::by_title`]
+  /// * `year` - Release year of the title, if known
+  /// * `filter` - Criteria the titles must satisfy
+  /// * `order` - Order to rank titles by
+  /// * `query` - Specifies if movies, series or both are queried
+  /// * `n` - Maximum number of titles to return
+  pub fn top_by_title(
+    &self,
+    title: &str,
+    year: Option<u16>,
+    filter: &Filter,
+    order: &SortOrder,
+    query: Query,
+    n: usize,
+  ) -> (Vec<&Title>, usize) {
+    let title = normalize_title(title);
+    let matches = |title: &&Title| filter.matches(title);
+
+    match year {
+      Some(year) => self.top_of(order, n, |db| db.by_title_and_year(&title, year, query).filter(matches)),
+      None => self.top_of(order, n, |db| db.by_title(&title, query).filter(matches)),
+    }
+  }
+
+  /// Query the best ranked titles by keywords and optionally year, without collecting all of
+  /// them
+  ///
+  /// Returns at most `n` titles and the total number of titles matching the keywords and
+  /// filter.
+  /// # Arguments
+  /// * `keywords` - Keywords to search in titles
+  /// * `year` - Release year of the title, if known
+  /// * `filter` - Criteria the titles must satisfy
+  /// * `order` - Order to rank titles by
+  /// * `query` - Specifies if movies, series or both are queried
+  /// * `n` - Maximum number of titles to return
+  pub fn top_by_keywords(
+    &self,
+    keywords: &Keywords,
+    year: Option<u16>,
+    filter: &Filter,
+    order: &SortOrder,
+    query: Query,
+    n: usize,
+  ) -> (Vec<&Title>, usize) {
+    let matches = |title: &&Title| filter.matches(title);
+
+    match year {
+      Some(year) => {
+        self.top_of(order, n, |db| db.by_keywords_and_year(keywords, year, query).filter(matches))
+      }
+      None => self.top_of(order, n, |db| db.by_keywords(keywords, query).filter(matches)),
     }
   }
 
   /// Query titles by title
+  ///
+  /// The title is normalized the same way title names are when they are indexed, so it does
+  /// not need to match their case, punctuation or accents. The databases are queried in
+  /// parallel, and all the matches are collected before they are returned. Use
+  /// [`Service::top_by_title`] to only keep the best ones.
   /// # Arguments
   /// * `title` - Title to be queried
-  /// * `query` - Specifies if movies or series are queried
-  pub fn by_title(&self, title: &str, query: Query) -> Vec<&Title> {
+  /// * `query` - Specifies if movies, series or both are queried
+  pub fn by_title<'a>(&'a self, title: &str, query: Query) -> impl Iterator<Item = &'a Title> + 'a {
+    let title = normalize_title(title);
     self
       .dbs
       .par_iter()
-      .map(|db| db.by_title(title, query).collect::<Vec<_>>())
-      .flatten()
-      .collect()
+      .flat_map_iter(|db| db.by_title(&title, query))
+      .collect::<Vec<_>>()
+      .into_iter()
   }
 
   /// Query titles by title and year
+  ///
+  /// The title is normalized the same way as in [`Service::by_title`]. The databases are
+  /// queried in parallel, and all the matches are collected before they are returned.
   /// # Arguments
   /// * `title` - Title to be queried
   /// * `year` - Release year of the title
-  /// * `query` - Specifies if movies or series are queried
-  pub fn by_title_and_year(&self, title: &str, year: u16, query: Query) -> Vec<&Title> {
+  /// * `query` - Specifies if movies, series or both are queried
+  pub fn by_title_and_year<'a>(
+    &'a self,
+    title: &str,
+    year: u16,
+    query: Query,
+  ) -> impl Iterator<Item = &'a Title> + 'a {
+    let title = normalize_title(title);
     self
       .dbs
       .par_iter()
-      .map(|db| db.by_title_and_year(title, year, query).collect::<Vec<_>>())
-      .flatten()
+      .flat_map_iter(|db| db.by_title_and_year(&title, year, query))
+      .collect::<Vec<_>>()
+      .into_iter()
+  }
+
+  /// Complete a partial title name, e.g. as it is being typed, returning the titles with the
+  /// most votes first
+  ///
+  /// The prefix is normalized the same way as in [`Service::by_title`]. The first call builds
+  /// the completion indexes, so it is much slower than the following ones.
+  /// # Arguments
+  /// * `prefix` - Beginning of the title name
+  /// * `limit` - Maximum number of titles to return
+  /// * `query` - Specifies if movies, series or both are queried
+  pub fn complete(&self, prefix: &str, limit: usize, query: Query) -> Vec<&Title> {
+    let prefix = normalize_title(prefix);
+    let mut titles: Vec<&Title> = self.dbs.iter().flat_map(|db| db.complete(&prefix, limit, query)).collect();
+    titles.sort_by_key(|title| Reverse(title.votes()));
+    titles.truncate(limit);
+    titles
+  }
+
+  /// Suggest titles similar to the title with the given ID, most similar first
+  ///
+  /// Titles are scored by the genres they share with the given title, how far apart they were
+  /// released, whether they are of the same type and their weighted rating. Only titles of
+  /// the same kind (movies or series) that share at least one genre are suggested, and none
+  /// are if the ID is not found.
+  /// # Arguments
+  /// * `id` - ID of the title to find similar titles to
+  /// * `prior` - Prior used to compute the weighted ratings of the titles
+  /// * `n` - Maximum number of titles to return
+  pub fn similar_to(&self, id: &TitleId, prior: &RatingPrior, n: usize) -> Vec<&Title> {
+    let title = match self.by_id(id, Query::All) {
+      Some(title) => title,
+      None => return Vec::new(),
+    };
+
+    let query = match title.kind() {
+      TitleKind::Movie => Query::Movies,
+      TitleKind::Series => Query::Series,
+    };
+
+    let tops = self
+      .dbs
+      .par_iter()
+      .map(|db| most_similar(title, db.titles(query), prior, n))
+      .collect::<Vec<_>>();
+
+    top_similar(tops.into_iter().flatten(), n)
+      .iter()
+      .map(|similar| similar.title())
       .collect()
   }
 
+  /// Pick titles matching a filter at random, in random order
+  /// # Arguments
+  /// * `filter` - Criteria the titles must satisfy
+  /// * `query` - Specifies if movies, series or both are picked from
+  /// * `n` - Number of titles to pick, fewer are returned if not enough titles match
+  /// * `random` - Random number generator, the same seed always picks the same titles
+  pub fn random(&self, filter: &Filter, query: Query, n: usize, random: &mut Random) -> Vec<&Title> {
+    let titles = self
+      .dbs
+      .iter()
+      .flat_map(|db| db.titles(query))
+      .filter(|title| filter.matches(title));
+    random.sample(titles, n)
+  }
+
   /// Query titles by keywords
+  ///
+  /// The databases are queried in parallel, and all the matches are collected before they are
+  /// returned. Use [`Service::top_by_keywords`] to only keep the best ones.
   /// # Arguments
-  /// * `keywords` - List of keywords to search in titles
-  /// * `query` - Specifies if movies or series are queried
-  pub fn by_keywords<'a, 'k>(&'a self, keywords: &'k [&str], query: Query) -> FnvHashSet<&'a Title> {
+  /// * `keywords` - Keywords to search in titles
+  /// * `query` - Specifies if movies, series or both are queried
+  pub fn by_keywords<'a>(
+    &'a self,
+    keywords: &Keywords,
+    query: Query,
+  ) -> impl Iterator<Item = &'a Title> + 'a {
     self
       .dbs
       .par_iter()
-      .map(|db| db.by_keywords(keywords, query).collect::<Vec<_>>())
-      .flatten()
-      .collect()
+      .flat_map_iter(|db| db.by_keywords(keywords, query))
+      .collect::<Vec<_>>()
+      .into_iter()
   }
 
   /// Query titles by keywords and year
+  ///
+  /// The databases are queried in parallel, and all the matches are collected before they are
+  /// returned.
   /// # Arguments
-  /// * `keywords` - List of keywords to search in titles
+  /// * `keywords` - Keywords to search in titles
   /// * `year` - Release year of the title
-  /// * `query` - Specifies if movies or series are queried
-  pub fn by_keywords_and_year<'a, 'k>(
+  /// * `query` - Specifies if movies, series or both are queried
+  pub fn by_keywords_and_year<'a>(
     &'a self,
-    keywords: &'k [&str],
+    keywords: &Keywords,
     year: u16,
     query: Query,
-  ) -> FnvHashSet<&'a Title> {
+  ) -> impl Iterator<Item = &'a Title> + 'a {
     self
       .dbs
       .par_iter()
-      .map(|db| db.by_keywords_and_year(keywords, year, query).collect::<Vec<_>>())
-      .flatten()
-      .collect()
+      .flat_map_iter(|db| db.by_keywords_and_year(keywords, year, query))
+      .collect::<Vec<_>>()
+      .into_iter()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::db::{Db, Query};
+  use crate::imdb::ratings::{Rating, Ratings};
+  use crate::imdb::service::{top_n, Datasets, Service, MAX_AGE, N_SHARDS};
+  use crate::imdb::sort::SortOrder;
+  use crate::imdb::title::Title;
+  use crate::imdb::title_id::TitleId;
+  use indoc::indoc;
+  use reqwest::Url;
+  use std::fs::{self, File};
+  use std::time::Duration;
+
+  #[test]
+  fn test_top_n() {
+    let mut ratings = Ratings::default();
+    ratings.insert(1, Rating::new(57, 1845));
+    ratings.insert(2, Rating::new(60, 236));
+    ratings.insert(3, Rating::new(65, 1603));
+    ratings.insert(4, Rating::new(60, 153));
+
+    let lines: [&[u8]; 5] = [
+      b"tt0000001\tshort\tCarmencita\tCarmencita\t0\t1894\t\\N\t1\tDocumentary,Short",
+      b"tt0000002\tshort\tLe clown et ses chiens\tLe clown et ses chiens\t0\t1892\t\\N\t5\tAnimation,Short",
+      b"tt0000003\tshort\tPauvre Pierrot\tPauvre Pierrot\t0\t1892\t\\N\t4\tAnimation,Comedy,Romance",
+      b"tt0000004\tshort\tUn bon bock\tUn bon bock\t0\t1892\t\\N\t12\tAnimation,Short",
+      b"tt0000005\tshort\tBlacksmith Scene\tBlacksmith Scene\t0\t1893\t\\N\t1\tComedy,Short",
+    ];
+
+    let titles: Vec<Title> = lines
+      .iter()
+      .map(|line| Option::<Title>::from(Title::from_tsv(line, &ratings).unwrap()).unwrap())
+      .collect();
+
+    let ids = |n| {
+      top_n(titles.iter(), &SortOrder::default(), n)
+        .0
+        .iter()
+        .map(|title| title.title_id().as_str().to_owned())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids(3), ["tt0000003", "tt0000002", "tt0000004"]);
+    assert_eq!(ids(10), ["tt0000003", "tt0000002", "tt0000004", "tt0000001", "tt0000005"]);
+    assert!(ids(0).is_empty());
+
+    let (_, total) =
+      top_n(titles.iter().filter(|title| title.start_year() == Some(1892)), &SortOrder::default(), 1);
+    assert_eq!(total, 3);
+  }
+
+  #[test]
+  fn test_datasets() {
+    let datasets = Datasets::default();
+    assert_eq!(datasets.url().unwrap().as_str(), "https://datasets.imdbws.com/");
+    assert_eq!(datasets.max_age(), MAX_AGE);
+    assert!(Service::file_needs_update(&None, &datasets).unwrap());
+
+    let file = || Some(File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap());
+    let datasets = Datasets::default().with_max_age(Duration::from_secs(60 * 60 * 24 * 365 * 100));
+    assert!(!Service::file_needs_update(&file(), &datasets).unwrap());
+    assert!(Service::file_needs_update(&file(), &datasets.with_force_update(true)).unwrap());
+    let datasets = Datasets::default().with_max_age(Duration::ZERO);
+    assert!(Service::file_needs_update(&file(), &datasets).unwrap());
+
+    let datasets = Datasets::default()
+      .with_url(Url::parse("http://localhost:8000/imdb").unwrap())
+      .with_max_age(Duration::from_secs(60 * 60 * 24));
+    assert_eq!(datasets.url().unwrap().as_str(), "http://localhost:8000/imdb/");
+    assert_eq!(
+      datasets.url().unwrap().join("title.basics.tsv.gz").unwrap().path(),
+      "/imdb/title.basics.tsv.gz"
+    );
+    assert_eq!(datasets.max_age(), Duration::from_secs(60 * 60 * 24));
+
+    let datasets = Datasets::default().with_url(Url::parse("http://localhost:8000/imdb/").unwrap());
+    assert_eq!(datasets.url().unwrap().as_str(), "http://localhost:8000/imdb/");
+  }
+
+  #[test]
+  fn test_custom_cache_dir_not_pruned() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    fs::create_dir(cache_dir.path().join("imdb")).unwrap();
+    fs::write(cache_dir.path().join("imdb.tvrankdb"), b"").unwrap();
+
+    // Nothing listens on port 1, so building the databases fails without any network access.
+    let datasets = Datasets::default()
+      .with_url(Url::parse("http://127.0.0.1:1/").unwrap())
+      .with_force_update(true);
+    assert!(Service::with_datasets(cache_dir.path(), &datasets, &|_, _| {}).is_err());
+
+    assert!(cache_dir.path().join("imdb").is_dir());
+    assert!(cache_dir.path().join("imdb.tvrankdb").is_file());
+
+    Service::remove_legacy_cache(cache_dir.path());
+    assert!(!cache_dir.path().join("imdb").exists());
+    assert!(!cache_dir.path().join("imdb.tvrankdb").exists());
+  }
+
+  #[test]
+  fn test_from_binary() {
+    let basics = indoc! {"
+      tconst\ttitleType\tprimaryTitle\toriginalTitle\tisAdult\tstartYear\tendYear\truntimeMinutes\tgenres
+      tt0000001\tmovie\tCarmencita\tCarmencita\t0\t1894\t\\N\t1\tDocumentary
+      tt0000002\tmovie\tMiss Jerry\tMiss Jerry\t0\t1894\t\\N\t40\tRomance
+      tt0000017\tmovie\tMiss Jerry\tMiss Jerry\t0\t1896\t\\N\t40\tRomance
+      tt0000018\ttvSeries\tThe Wire\tThe Wire\t0\t2002\t2008\t59\tCrime,Drama
+    "};
+    let ratings = "tconst\taverageRating\tnumVotes\n";
+
+    let mut movies_data = Vec::new();
+    let mut series_data = Vec::new();
+    Db::to_binary(ratings.as_bytes(), basics.as_bytes(), &mut movies_data, &mut series_data).unwrap();
+
+    let service = Service::from_binary(
+      Box::leak(movies_data.into_boxed_slice()),
+      Box::leak(series_data.into_boxed_slice()),
+    );
+    assert_eq!(service.dbs.len(), N_SHARDS);
+
+    let id = |id| TitleId::try_from(id).unwrap();
+    let title = service.by_id(&id("tt0000017"), Query::Movies).unwrap();
+    assert_eq!(title.start_year(), Some(1896));
+    assert!(service.by_id(&id("tt0000017"), Query::Series).is_none());
+    assert!(service.by_id(&id("tt0000018"), Query::All).is_some());
+    assert!(service.by_id(&id("tt0000003"), Query::All).is_none());
+    assert_eq!(service.by_title("miss jerry", Query::Movies).count(), 2);
   }
 }
@@ -0,0 +1,188 @@
+#![warn(clippy::all)]
+
+use crate::imdb::ratings::RatingPrior;
+use crate::imdb::title::Title;
+use crate::utils::top::top_n_by;
+use std::cmp::Ordering;
+
+/// How much sharing genres contributes to the similarity of two titles.
+const GENRES_WEIGHT: f32 = 0.5;
+
+/// How much being released around the same time contributes to the similarity of two titles.
+const ERA_WEIGHT: f32 = 0.2;
+
+/// How much being of the same type (e.g. both mini-series) contributes to the similarity of
+/// two titles.
+const TYPE_WEIGHT: f32 = 0.1;
+
+/// How much the weighted rating of a title contributes to its similarity to any other title,
+/// so that well-received titles are suggested first.
+const QUALITY_WEIGHT: f32 = 0.2;
+
+/// Titles released this many years apart or more are not considered to be of the same era.
+const ERA_YEARS: f32 = 20.0;
+
+/// Score how similar a title is to a reference title, between 0 and 1.
+///
+/// The score is based on the genres the titles share, how far apart they were released,
+/// whether they are of the same type and how well received the title is. Returns `None` if
+/// the titles are the same or do not share any genre.
+///
+/// # Arguments
+///
+/// * `reference` - The title to compare with.
+/// * `title` - The title to score.
+/// * `prior` - The prior used to compute weighted ratings.
+pub(crate) fn similarity(reference: &Title, title: &Title, prior: &RatingPrior) -> Option<f32> {
+  if reference.title_id() == title.title_id() {
+    return None;
+  }
+
+  let shared = reference.genres().intersection(title.genres()).len();
+
+  if shared == 0 {
+    return None;
+  }
+
+  let genres = shared as f32 / reference.genres().union(title.genres()).len() as f32;
+
+  let era = match (reference.start_year(), title.start_year()) {
+    (Some(a), Some(b)) => (1.0 - (f32::from(a) - f32::from(b)).abs() / ERA_YEARS).max(0.0),
+    _ => 0.0,
+  };
+
+  let same_type = if reference.title_type() == title.title_type() {
+    1.0
+  } else {
+    0.0
+  };
+  let quality = title.weighted_rating(prior).unwrap_or(0.0) / 100.0;
+
+  Some(GENRES_WEIGHT * genres + ERA_WEIGHT * era + TYPE_WEIGHT * same_type + QUALITY_WEIGHT * quality)
+}
+
+/// A title along with its similarity score.
+#[derive(Clone, Copy)]
+pub(crate) struct Similar<'a, 'storage> {
+  score: f32,
+  title: &'a Title<'storage>,
+}
+
+impl<'a, 'storage> Similar<'a, 'storage> {
+  /// The title.
+  pub(crate) fn title(&self) -> &'a Title<'storage> {
+    self.title
+  }
+
+  /// Compare with another title, more similar titles being greater and ties being broken by
+  /// the number of votes.
+  ///
+  /// # Arguments
+  ///
+  /// * `other` - The title to compare with.
+  fn compare(&self, other: &Self) -> Ordering {
+    self
+      .score
+      .partial_cmp(&other.score)
+      .unwrap_or(Ordering::Equal)
+      .then_with(|| self.title.votes().cmp(&other.title.votes()))
+  }
+}
+
+/// Return the `n` titles most similar to a reference title, most similar first.
+///
+/// # Arguments
+///
+/// * `reference` - The title to compare with.
+/// * `titles` - The titles to choose from.
+/// * `prior` - The prior used to compute weighted ratings.
+/// * `n` - The maximum number of titles to return.
+pub(crate) fn most_similar<'a, 'storage>(
+  reference: &Title,
+  titles: impl Iterator<Item = &'a Title<'storage>>,
+  prior: &RatingPrior,
+  n: usize,
+) -> Vec<Similar<'a, 'storage>> {
+  let similar =
+    titles.filter_map(|title| similarity(reference, title, prior).map(|score| Similar { score, title }));
+  top_similar(similar, n)
+}
+
+/// Return the `n` most similar titles, most similar first.
+///
+/// # Arguments
+///
+/// * `similar` - The titles to choose from, along with their scores.
+/// * `n` - The maximum number of titles to return.
+pub(crate) fn top_similar<'a, 'storage>(
+  similar: impl Iterator<Item = Similar<'a, 'storage>>,
+  n: usize,
+) -> Vec<Similar<'a, 'storage>> {
+  let (top, _) = top_n_by(similar, n, Similar::compare);
+  top
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::ratings::{Rating, RatingPrior, Ratings};
+  use crate::imdb::similarity::{most_similar, similarity};
+  use crate::imdb::title::Title;
+
+  fn make_titles(ratings: &Ratings) -> Vec<Title<'static>> {
+    let lines: [&'static [u8]; 5] = [
+      b"tt0000001\tmovie\tThe Matrix\tThe Matrix\t0\t1999\t\\N\t136\tAction,Sci-Fi",
+      b"tt0000002\tmovie\tDark City\tDark City\t0\t1998\t\\N\t100\tDrama,Mystery,Sci-Fi",
+      b"tt0000003\tmovie\tInception\tInception\t0\t2010\t\\N\t148\tAction,Adventure,Sci-Fi",
+      b"tt0000004\tmovie\tNotting Hill\tNotting Hill\t0\t1999\t\\N\t124\tComedy,Romance",
+      b"tt0000005\ttvMovie\tThe Matrix Remade\tThe Matrix Remade\t0\t1999\t\\N\t90\tAction,Sci-Fi",
+    ];
+
+    lines
+      .iter()
+      .map(|line| Option::<Title>::from(Title::from_tsv(line, ratings).unwrap()).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn test_similarity() {
+    let titles = make_titles(&Ratings::default());
+    let prior = RatingPrior::default();
+
+    assert!(similarity(&titles[0], &titles[0], &prior).is_none());
+    assert!(similarity(&titles[0], &titles[3], &prior).is_none());
+
+    let dark_city = similarity(&titles[0], &titles[1], &prior).unwrap();
+    let inception = similarity(&titles[0], &titles[2], &prior).unwrap();
+    let remade = similarity(&titles[0], &titles[4], &prior).unwrap();
+    assert!(remade > inception);
+    assert!(remade > dark_city);
+    assert!((0.0..=1.0).contains(&remade));
+
+    // The weighted rating of titles with few votes leans towards the rating of the prior.
+    let mut ratings = Ratings::default();
+    ratings.insert(5, Rating::new(60, 1_000));
+    let titles = make_titles(&ratings);
+    let remade = |prior| similarity(&titles[0], &titles[4], &prior).unwrap();
+    assert!(remade(RatingPrior::new(90, 100_000)) > remade(RatingPrior::new(30, 100_000)));
+  }
+
+  #[test]
+  fn test_most_similar() {
+    let mut ratings = Ratings::default();
+    ratings.insert(2, Rating::new(76, 200_000));
+    ratings.insert(3, Rating::new(88, 2_000_000));
+    ratings.insert(5, Rating::new(60, 1_000));
+
+    let titles = make_titles(&ratings);
+    let ids = |n| {
+      most_similar(&titles[0], titles.iter(), &RatingPrior::default(), n)
+        .iter()
+        .map(|similar| similar.title().title_id().as_str())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ids(10), ["tt0000005", "tt0000003", "tt0000002"]);
+    assert_eq!(ids(1), ["tt0000005"]);
+    assert!(ids(0).is_empty());
+  }
+}
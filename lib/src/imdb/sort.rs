@@ -0,0 +1,309 @@
+#![warn(clippy::all)]
+
+use crate::imdb::error::Err;
+use crate::imdb::ratings::RatingPrior;
+use crate::imdb::title::Title;
+use crate::utils::tokens::names_match;
+use derive_more::Display;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A key titles can be sorted by.
+#[derive(Debug, Display, PartialEq, Eq, Clone, Copy)]
+#[display(fmt = "{}")]
+pub enum SortKey {
+  /// The primary title.
+  #[display(fmt = "title")]
+  Title,
+  /// The runtime.
+  #[display(fmt = "runtime")]
+  Runtime,
+  /// The number of votes.
+  #[display(fmt = "votes")]
+  Votes,
+  /// The rating, then the number of votes.
+  #[display(fmt = "rating")]
+  Rating,
+  /// The rating weighted by the number of votes.
+  #[display(fmt = "weighted")]
+  WeightedRating,
+  /// The release year.
+  #[display(fmt = "year")]
+  Year,
+}
+
+impl SortKey {
+  /// All the keys titles can be sorted by.
+  const ALL: [SortKey; 6] =
+    [Self::Title, Self::Runtime, Self::Votes, Self::Rating, Self::WeightedRating, Self::Year];
+
+  /// The direction titles are sorted in when none is given: alphabetically for titles and
+  /// highest first for everything else.
+  pub fn default_direction(&self) -> SortDirection {
+    match self {
+      SortKey::Title => SortDirection::Asc,
+      _ => SortDirection::Desc,
+    }
+  }
+}
+
+impl FromStr for SortKey {
+  type Err = Err;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if names_match(s, "weighted rating") {
+      return Ok(SortKey::WeightedRating);
+    }
+
+    Self::ALL
+      .into_iter()
+      .find(|key| names_match(s, &key.to_string()))
+      .ok_or_else(|| Err::SortKey(s.to_owned()))
+  }
+}
+
+/// The direction titles are sorted in.
+#[derive(Debug, Display, PartialEq, Eq, Clone, Copy)]
+#[display(fmt = "{}")]
+pub enum SortDirection {
+  /// Lowest (or alphabetically first) first.
+  #[display(fmt = "asc")]
+  Asc,
+  /// Highest (or alphabetically last) first.
+  #[display(fmt = "desc")]
+  Desc,
+}
+
+impl FromStr for SortDirection {
+  type Err = Err;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.to_lowercase().as_str() {
+      "asc" | "ascending" => Ok(SortDirection::Asc),
+      "desc" | "descending" => Ok(SortDirection::Desc),
+      _ => Err(Err::SortDirection(s.to_owned())),
+    }
+  }
+}
+
+/// Compare two optional values in the given direction. Unknown values always come last.
+///
+/// # Arguments
+///
+/// * `a` - The first value.
+/// * `b` - The second value.
+/// * `direction` - The direction to compare known values in.
+fn compare<T: PartialOrd>(a: Option<T>, b: Option<T>, direction: SortDirection) -> Ordering {
+  match (a, b) {
+    (Some(a), Some(b)) => {
+      let ord = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+
+      match direction {
+        SortDirection::Asc => ord,
+        SortDirection::Desc => ord.reverse(),
+      }
+    }
+    (Some(_), None) => Ordering::Less,
+    (None, Some(_)) => Ordering::Greater,
+    (None, None) => Ordering::Equal,
+  }
+}
+
+/// An ordering of titles, made of keys and the direction to sort each of them in.
+///
+/// Titles are compared by each key in turn, until one of the keys tells them apart. An
+/// ordering can be parsed from a comma-separated list of `KEY[:DIRECTION]` pairs, e.g.
+/// `votes:desc,year:asc` or `rating,title`, where `KEY` is one of `title`, `runtime`,
+/// `votes`, `rating`, `weighted` or `year` and `DIRECTION` is `asc` or `desc`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SortOrder {
+  keys: Vec<(SortKey, SortDirection)>,
+  prior: RatingPrior,
+}
+
+impl Default for SortOrder {
+  fn default() -> Self {
+    Self::new(vec![
+      (SortKey::Rating, SortDirection::Desc),
+      (SortKey::Year, SortDirection::Desc),
+      (SortKey::Title, SortDirection::Desc),
+    ])
+  }
+}
+
+impl FromStr for SortOrder {
+  type Err = Err;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut keys = Vec::new();
+
+    for key in s.split(',').map(str::trim).filter(|key| !key.is_empty()) {
+      let (key, direction) = match key.split_once(':') {
+        Some((key, direction)) => (key.parse::<SortKey>()?, direction.parse()?),
+        None => {
+          let key = key.parse::<SortKey>()?;
+          (key, key.default_direction())
+        }
+      };
+
+      keys.push((key, direction));
+    }
+
+    if keys.is_empty() {
+      return Err(Err::SortKey(s.to_owned()));
+    }
+
+    Ok(Self::new(keys))
+  }
+}
+
+impl fmt::Display for SortOrder {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, (key, direction)) in self.keys.iter().enumerate() {
+      if i > 0 {
+        write!(f, ",")?;
+      }
+
+      write!(f, "{key}:{direction}")?;
+    }
+
+    Ok(())
+  }
+}
+
+impl SortOrder {
+  /// Create an ordering from a list of keys and directions.
+  ///
+  /// # Arguments
+  ///
+  /// * `keys` - The keys to compare titles by, most significant first.
+  pub fn new(keys: Vec<(SortKey, SortDirection)>) -> Self {
+    Self { keys, prior: RatingPrior::default() }
+  }
+
+  /// Use the given prior to compute weighted ratings.
+  ///
+  /// # Arguments
+  ///
+  /// * `prior` - Prior rating and number of votes.
+  pub fn with_prior(mut self, prior: RatingPrior) -> Self {
+    self.prior = prior;
+    self
+  }
+
+  /// The keys titles are compared by, and the direction of each.
+  pub fn keys(&self) -> &[(SortKey, SortDirection)] {
+    &self.keys
+  }
+
+  /// The prior used to compute weighted ratings.
+  pub fn prior(&self) -> &RatingPrior {
+    &self.prior
+  }
+
+  /// Compare two titles, returning `Ordering::Less` if `a` comes first.
+  ///
+  /// # Arguments
+  ///
+  /// * `a` - The first title.
+  /// * `b` - The second title.
+  pub fn compare(&self, a: &Title, b: &Title) -> Ordering {
+    for &(key, direction) in &self.keys {
+      let ord = match key {
+        SortKey::Title => compare(Some(a.primary_title()), Some(b.primary_title()), direction),
+        SortKey::Runtime => compare(a.runtime(), b.runtime(), direction),
+        SortKey::Votes => compare(Some(a.votes()), Some(b.votes()), direction),
+        SortKey::Rating => compare(a.rating(), b.rating(), direction),
+        SortKey::WeightedRating => {
+          compare(a.weighted_rating(&self.prior), b.weighted_rating(&self.prior), direction)
+        }
+        SortKey::Year => compare(a.start_year(), b.start_year(), direction),
+      };
+
+      if ord != Ordering::Equal {
+        return ord;
+      }
+    }
+
+    Ordering::Equal
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::imdb::ratings::{Rating, RatingPrior, Ratings};
+  use crate::imdb::sort::{SortDirection, SortKey, SortOrder};
+  use crate::imdb::title::Title;
+  use std::cmp::Ordering;
+
+  fn make_title(line: &'static [u8], ratings: &Ratings) -> Title<'static> {
+    Option::<Title>::from(Title::from_tsv(line, ratings).unwrap()).unwrap()
+  }
+
+  fn order(keys: &[(SortKey, SortDirection)]) -> SortOrder {
+    SortOrder::new(keys.to_vec())
+  }
+
+  #[test]
+  fn test_weighted_rating() {
+    let mut ratings = Ratings::default();
+    ratings.insert(1, Rating::new(89, 12));
+    ratings.insert(2, Rating::new(87, 900_000));
+
+    let few_votes = make_title(b"tt0000001\tmovie\tFew Votes\tFew Votes\t0\t2000\t\\N\t90\tDrama", &ratings);
+    let many_votes =
+      make_title(b"tt0000002\tmovie\tMany Votes\tMany Votes\t0\t2000\t\\N\t90\tDrama", &ratings);
+
+    let rating = order(&[(SortKey::Rating, SortDirection::Desc)]);
+    assert_eq!(rating.compare(&few_votes, &many_votes), Ordering::Less);
+
+    let weighted = order(&[(SortKey::WeightedRating, SortDirection::Desc)]);
+    assert_eq!(weighted.compare(&few_votes, &many_votes), Ordering::Greater);
+
+    let weighted = weighted.with_prior(RatingPrior::new(70, 0));
+    assert_eq!(weighted.compare(&few_votes, &many_votes), Ordering::Less);
+  }
+
+  #[test]
+  fn test_keys_and_directions() {
+    let mut ratings = Ratings::default();
+    ratings.insert(1, Rating::new(80, 100));
+    ratings.insert(2, Rating::new(80, 200));
+
+    let old = make_title(b"tt0000001\tmovie\tOld\tOld\t0\t1990\t\\N\t120\tDrama", &ratings);
+    let new = make_title(b"tt0000002\tmovie\tNew\tNew\t0\t2000\t\\N\t90\tDrama", &ratings);
+    let unknown = make_title(b"tt0000003\tmovie\tUnknown\tUnknown\t0\t\\N\t\\N\t\\N\tDrama", &ratings);
+
+    assert_eq!(SortOrder::default().compare(&old, &new), Ordering::Greater);
+    assert_eq!(order(&[(SortKey::Rating, SortDirection::Asc)]).compare(&old, &new), Ordering::Less);
+    assert_eq!(order(&[(SortKey::Votes, SortDirection::Desc)]).compare(&old, &new), Ordering::Greater);
+    assert_eq!(order(&[(SortKey::Runtime, SortDirection::Desc)]).compare(&old, &new), Ordering::Less);
+    assert_eq!(order(&[(SortKey::Title, SortDirection::Asc)]).compare(&old, &new), Ordering::Greater);
+    assert_eq!(order(&[(SortKey::Year, SortDirection::Asc)]).compare(&old, &new), Ordering::Less);
+    assert_eq!(order(&[(SortKey::Year, SortDirection::Asc)]).compare(&unknown, &old), Ordering::Greater);
+    assert_eq!(order(&[(SortKey::Year, SortDirection::Desc)]).compare(&unknown, &old), Ordering::Greater);
+  }
+
+  #[test]
+  fn test_parse() {
+    let order: SortOrder = "votes:desc, year:ASC,title".parse().unwrap();
+    assert_eq!(
+      order.keys(),
+      [
+        (SortKey::Votes, SortDirection::Desc),
+        (SortKey::Year, SortDirection::Asc),
+        (SortKey::Title, SortDirection::Asc)
+      ]
+    );
+    assert_eq!(order.to_string(), "votes:desc,year:asc,title:asc");
+
+    let order: SortOrder = "weighted-rating".parse().unwrap();
+    assert_eq!(order.keys(), [(SortKey::WeightedRating, SortDirection::Desc)]);
+    assert_eq!(order, order.to_string().parse().unwrap());
+
+    assert!("".parse::<SortOrder>().is_err());
+    assert!("genre".parse::<SortOrder>().is_err());
+    assert!("year:up".parse::<SortOrder>().is_err());
+  }
+}
@@ -2,10 +2,10 @@
 
 use crate::imdb::error::Err;
 use crate::imdb::genre::{Genre, Genres};
-use crate::imdb::ratings::{Rating, Ratings};
+use crate::imdb::ratings::{Rating, RatingPrior, Ratings};
 use crate::imdb::title_header::TitleHeader;
 use crate::imdb::title_id::TitleId;
-use crate::imdb::title_type::TitleType;
+use crate::imdb::title_type::{TitleKind, TitleType};
 use crate::imdb::tokens;
 use crate::iter_next;
 use crate::utils::result::Res;
@@ -70,6 +70,11 @@ impl<'storage> Title<'storage> {
     self.header.title_type()
   }
 
+  /// Returns whether the title is a movie or a series
+  pub fn kind(&self) -> TitleKind {
+    self.header.kind()
+  }
+
   /// Returns the primary title in English
   pub fn primary_title(&self) -> &str {
     self.primary_title
@@ -108,6 +113,18 @@ impl<'storage> Title<'storage> {
     self.header.rating()
   }
 
+  /// Returns the rating of the title weighted by its number of votes
+  /// # Arguments
+  /// * `prior` - Prior rating and number of votes
+  pub fn weighted_rating(&self, prior: &RatingPrior) -> Option<f32> {
+    self.rating().map(|rating| rating.weighted(prior))
+  }
+
+  /// Returns the number of votes of the title, or 0 if it has no rating
+  pub fn votes(&self) -> u32 {
+    self.rating().map_or(0, |rating| rating.votes())
+  }
+
   /// Reads a title from tab separated values and returns it inside a TsvAction struct
   /// # Arguments
   /// * `line` - A title as tab separated values
@@ -2,7 +2,7 @@
 
 use crate::imdb::genre::Genres;
 use crate::imdb::ratings::Rating;
-use crate::imdb::title_type::TitleType;
+use crate::imdb::title_type::{TitleKind, TitleType};
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 use std::ops::Deref;
@@ -37,12 +37,13 @@ impl Serialize for TitleHeader {
   where
     S: Serializer,
   {
-    let mut state = serializer.serialize_struct("TitleHeader", 5)?;
+    let mut state = serializer.serialize_struct("TitleHeader", 6)?;
     state.serialize_field("rating", &self.rating())?;
     state.serialize_field("start_year", &self.start_year())?;
     state.serialize_field("runtime", &self.runtime_minutes())?;
     state.serialize_field("genres", &self.genres())?;
     state.serialize_field("title_type", &self.title_type())?;
+    state.serialize_field("kind", &self.kind())?;
     state.end()
   }
 }
@@ -179,6 +180,13 @@ impl TitleHeader {
     unsafe { TitleType::from(value as u8) }
   }
 
+  /// Returns whether the title is a movie or a series
+  ///
+  /// Only movies and series are stored, so any other type of title is considered a movie.
+  pub(crate) fn kind(&self) -> TitleKind {
+    self.title_type().kind().unwrap_or(TitleKind::Movie)
+  }
+
   /// Returns the set of genres the title is associated with
   pub(crate) fn genres(&self) -> Genres {
     let mask = (2_u128.pow(32) - 1) << 68;
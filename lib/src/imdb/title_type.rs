@@ -1,10 +1,24 @@
 #![warn(clippy::all)]
 
+use crate::utils::tokens::names_match;
 use derive_more::Display;
 use enum_utils::FromStr;
 use serde::Serialize;
 use std::hash::Hash;
 
+/// Whether a title is a movie or a series
+#[derive(Debug, Display, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[display(fmt = "{}")]
+pub enum TitleKind {
+  /// Movie
+  #[display(fmt = "movie")]
+  Movie,
+  /// Series
+  #[display(fmt = "series")]
+  Series,
+}
+
 /// Encodes the 13 types a title can be
 #[derive(Debug, Display, FromStr, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
 #[enumeration(rename_all = "camelCase")]
@@ -62,6 +76,31 @@ impl TitleType {
     std::mem::transmute(value)
   }
 
+  /// Returns an iterator over all the TitleType items
+  pub fn all() -> impl Iterator<Item = Self> {
+    (0..=TitleType::RadioSeries as u8).map(|value| unsafe { Self::from(value) })
+  }
+
+  /// Looks up a TitleType by name, ignoring case and punctuation (e.g. "tvSeries" or "TV Series")
+  /// # Arguments
+  /// * `name` - Name of the title type
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::all().find(|title_type| {
+      names_match(name, &format!("{:?}", title_type)) || names_match(name, &title_type.to_string())
+    })
+  }
+
+  /// Returns whether the TitleType is a movie or a series, if it is either
+  pub fn kind(&self) -> Option<TitleKind> {
+    if self.is_movie() {
+      Some(TitleKind::Movie)
+    } else if self.is_series() {
+      Some(TitleKind::Series)
+    } else {
+      None
+    }
+  }
+
   /// Returns true if the TitleType is movie
   pub(crate) fn is_movie(&self) -> bool {
     match self {
@@ -115,7 +154,7 @@ impl TitleType {
 
 #[cfg(test)]
 mod tests {
-  use crate::imdb::title_type::TitleType;
+  use crate::imdb::title_type::{TitleKind, TitleType};
 
   #[test]
   fn test_title_type_value() {
@@ -151,6 +190,25 @@ mod tests {
     assert_eq!(TitleType::RadioSeries, unsafe { TitleType::from(12) });
   }
 
+  #[test]
+  fn test_title_type_from_name() {
+    assert_eq!(TitleType::from_name("movie"), Some(TitleType::Movie));
+    assert_eq!(TitleType::from_name("tvSeries"), Some(TitleType::TvSeries));
+    assert_eq!(TitleType::from_name("TV Mini-Series"), Some(TitleType::TvMiniSeries));
+    assert_eq!(TitleType::from_name("short movie"), Some(TitleType::Short));
+    assert_eq!(TitleType::from_name("short"), Some(TitleType::Short));
+    assert_eq!(TitleType::from_name("documentary"), None);
+    assert_eq!(TitleType::all().count(), 13);
+  }
+
+  #[test]
+  fn test_kind() {
+    assert_eq!(TitleType::Movie.kind(), Some(TitleKind::Movie));
+    assert_eq!(TitleType::TvSpecial.kind(), Some(TitleKind::Movie));
+    assert_eq!(TitleType::TvMiniSeries.kind(), Some(TitleKind::Series));
+    assert_eq!(TitleType::TvEpisode.kind(), None);
+  }
+
   #[test]
   fn test_is_movie() {
     assert!(unsafe { !TitleType::from(0).is_movie() });
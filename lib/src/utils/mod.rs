@@ -4,5 +4,8 @@
 //! Common utilities for things like parsing and IO.
 
 pub mod io;
+pub mod normalize;
+pub mod random;
 pub mod result;
 pub mod tokens;
+pub mod top;
@@ -0,0 +1,175 @@
+#![warn(clippy::all)]
+
+//! Common utilities for normalizing titles, so that different spellings of the same title
+//! can be matched against each other.
+//!
+//! The same normalization is applied to title names when they are indexed and to search
+//! queries, so callers of the library never need to normalize queries themselves.
+
+use deunicode::deunicode;
+
+/// Articles that are moved or dropped when normalizing titles.
+const ARTICLES: [&str; 3] = ["the", "a", "an"];
+
+/// Roman numerals for the units, used to recognize numerals like `ii` or `xiv`.
+const ROMAN_UNITS: [&str; 10] = ["", "i", "ii", "iii", "iv", "v", "vi", "vii", "viii", "ix"];
+
+/// Whether a normalized word is an article that is dropped from the start of titles.
+///
+/// # Arguments
+///
+/// * `word` - The word to check.
+pub fn is_article(word: &str) -> bool {
+  ARTICLES.contains(&word)
+}
+
+/// Convert a lowercase roman numeral between 2 and 39 (e.g. `ii`, `xiv`) to its value.
+///
+/// Single-letter numerals are left alone since they are far more often words or letters than
+/// numbers (e.g. "I, Robot", "V for Vendetta" and "Malcolm X").
+///
+/// # Arguments
+///
+/// * `word` - The word to convert.
+fn roman_to_arabic(word: &str) -> Option<u8> {
+  if word.len() < 2 {
+    return None;
+  }
+
+  let units = word.trim_start_matches('x');
+  let tens = word.len() - units.len();
+
+  if tens > 3 {
+    return None;
+  }
+
+  let units = ROMAN_UNITS.iter().position(|&numeral| numeral == units)?;
+  Some((tens * 10 + units) as u8)
+}
+
+/// Normalize a piece of text into lowercase space-separated words.
+///
+/// The following transformations are applied:
+///
+/// * Text is transliterated to ASCII (e.g. "Amélie" becomes "amelie" and "Брат" becomes
+///   "brat").
+/// * Letters are lowercased.
+/// * Apostrophes and periods are dropped (e.g. "Ocean's" becomes "oceans" and "S.W.A.T."
+///   becomes "swat").
+/// * `&` is replaced by "and".
+/// * Any other punctuation is treated as a word separator (e.g. "Spider-Man" becomes "spider
+///   man").
+/// * Roman numerals are replaced by arabic numerals (e.g. "Rocky II" becomes "rocky 2").
+///
+/// # Arguments
+///
+/// * `text` - The text to normalize.
+pub fn normalize_words(text: &str) -> String {
+  let text = deunicode(text);
+  let mut folded = String::with_capacity(text.len());
+
+  for c in text.chars() {
+    match c {
+      '\'' | '’' | '.' => {}
+      '&' => folded.push_str(" and "),
+      c if c.is_ascii_alphanumeric() => folded.push(c.to_ascii_lowercase()),
+      _ => folded.push(' '),
+    }
+  }
+
+  let mut result = String::with_capacity(folded.len());
+
+  for word in folded.split_whitespace() {
+    if !result.is_empty() {
+      result.push(' ');
+    }
+
+    match roman_to_arabic(word) {
+      Some(value) => result.push_str(&value.to_string()),
+      None => result.push_str(word),
+    }
+  }
+
+  result
+}
+
+/// Normalize a title so that different spellings of it can be matched.
+///
+/// On top of the transformations done by [`normalize_words`], leading articles are dropped
+/// (e.g. "The Matrix" becomes "matrix") and so are trailing articles that follow a comma, as
+/// found in sorted listings (e.g. "Matrix, The" also becomes "matrix").
+///
+/// # Arguments
+///
+/// * `title` - The title to normalize.
+pub fn normalize_title(title: &str) -> String {
+  let mut title = title.trim();
+
+  if let Some((rest, article)) = title.rsplit_once(',') {
+    if ARTICLES.iter().any(|&a| article.trim().eq_ignore_ascii_case(a)) {
+      title = rest;
+    }
+  }
+
+  let words = normalize_words(title);
+
+  if let Some((article, rest)) = words.split_once(' ') {
+    if is_article(article) {
+      return rest.to_owned();
+    }
+  }
+
+  words
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::normalize::{normalize_title, normalize_words, roman_to_arabic};
+
+  #[test]
+  fn test_roman_to_arabic() {
+    assert_eq!(roman_to_arabic("i"), None);
+    assert_eq!(roman_to_arabic("ii"), Some(2));
+    assert_eq!(roman_to_arabic("iv"), Some(4));
+    assert_eq!(roman_to_arabic("v"), None);
+    assert_eq!(roman_to_arabic("x"), None);
+    assert_eq!(roman_to_arabic("vi"), Some(6));
+    assert_eq!(roman_to_arabic("xiv"), Some(14));
+    assert_eq!(roman_to_arabic("xxxix"), Some(39));
+    assert_eq!(roman_to_arabic("xxxx"), None);
+    assert_eq!(roman_to_arabic("iiii"), None);
+    assert_eq!(roman_to_arabic("vx"), None);
+    assert_eq!(roman_to_arabic("mix"), None);
+  }
+
+  #[test]
+  fn test_normalize_words() {
+    assert_eq!(normalize_words("Mission: Impossible - Fallout"), "mission impossible fallout");
+    assert_eq!(normalize_words("Mission Impossible - Fallout"), "mission impossible fallout");
+    assert_eq!(normalize_words("Ocean's Eleven"), "oceans eleven");
+    assert_eq!(normalize_words("S.W.A.T."), "swat");
+    assert_eq!(normalize_words("Fast & Furious"), "fast and furious");
+    assert_eq!(normalize_words("Rocky II"), "rocky 2");
+    assert_eq!(normalize_words("Rocky 2"), "rocky 2");
+    assert_eq!(normalize_words("Malcolm X"), "malcolm x");
+    assert_eq!(normalize_words("V for Vendetta"), "v for vendetta");
+    assert_eq!(normalize_words("Amélie"), "amelie");
+    assert_eq!(normalize_words("Le Fabuleux Destin d’Amélie Poulain"), "le fabuleux destin damelie poulain");
+    assert_eq!(normalize_words("Amelie Poulaín"), normalize_words("Amélie Poulain"));
+    assert_eq!(normalize_words("Брат"), "brat");
+    assert_eq!(normalize_words("Who Framed Roger Rabbit?"), "who framed roger rabbit");
+    assert_eq!(normalize_words("  "), "");
+  }
+
+  #[test]
+  fn test_normalize_title() {
+    assert_eq!(normalize_title("The Matrix"), "matrix");
+    assert_eq!(normalize_title("Matrix, The"), "matrix");
+    assert_eq!(normalize_title("A Beautiful Mind"), "beautiful mind");
+    assert_eq!(normalize_title("Beautiful Mind, A"), "beautiful mind");
+    assert_eq!(normalize_title("The"), "the");
+    assert_eq!(normalize_title("Hello, Dolly!"), "hello dolly");
+    assert_eq!(normalize_title("The Godfather: Part III"), "godfather part 3");
+    assert_eq!(normalize_title("The Godfather Part 3"), "godfather part 3");
+  }
+}
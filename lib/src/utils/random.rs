@@ -0,0 +1,126 @@
+#![warn(clippy::all)]
+
+//! A small, seedable pseudo-random number generator, used to pick random titles.
+//!
+//! The generator is SplitMix64, which is fast and whose output only depends on the seed, so
+//! the same seed always picks the same titles from the same database.
+
+/// A seedable pseudo-random number generator (SplitMix64).
+///
+/// It is not suitable for cryptographic purposes.
+#[derive(Debug, Clone)]
+pub struct Random {
+  state: u64,
+}
+
+impl Random {
+  /// Create a generator from a seed.
+  ///
+  /// # Arguments
+  ///
+  /// * `seed` - The seed. Generators created from the same seed produce the same numbers.
+  pub fn new(seed: u64) -> Self {
+    Self { state: seed }
+  }
+
+  /// Return the next pseudo-random number.
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /// Return a pseudo-random number in `0..n`, or `0` if `n` is `0`.
+  ///
+  /// # Arguments
+  ///
+  /// * `n` - The upper bound (exclusive).
+  pub fn below(&mut self, n: usize) -> usize {
+    ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+  }
+
+  /// Shuffle the given items in place.
+  ///
+  /// # Arguments
+  ///
+  /// * `items` - The items to shuffle.
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      items.swap(i, self.below(i + 1));
+    }
+  }
+
+  /// Pick `n` items at random, without replacement and in random order, going through the
+  /// items only once. Returns all the items, shuffled, if there are fewer than `n` of them.
+  ///
+  /// # Arguments
+  ///
+  /// * `items` - The items to pick from.
+  /// * `n` - The number of items to pick.
+  pub fn sample<T>(&mut self, items: impl IntoIterator<Item = T>, n: usize) -> Vec<T> {
+    // `n` may be far larger than the number of items, so the capacity is not reserved up front.
+    let mut picked = Vec::new();
+
+    for (i, item) in items.into_iter().enumerate() {
+      if i < n {
+        picked.push(item);
+      } else {
+        let j = self.below(i + 1);
+
+        if j < n {
+          picked[j] = item;
+        }
+      }
+    }
+
+    self.shuffle(&mut picked);
+    picked
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::random::Random;
+
+  #[test]
+  fn test_seed() {
+    let numbers = |seed| {
+      let mut random = Random::new(seed);
+      (0..5).map(|_| random.next_u64()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(numbers(42), numbers(42));
+    assert_ne!(numbers(42), numbers(43));
+    assert_eq!(Random::new(0).next_u64(), 0xe220_a839_7b1d_cdaf);
+  }
+
+  #[test]
+  fn test_below() {
+    let mut random = Random::new(7);
+    assert!((0..1000).all(|_| random.below(10) < 10));
+    assert_eq!(random.below(0), 0);
+    assert_eq!(random.below(1), 0);
+  }
+
+  #[test]
+  fn test_sample() {
+    let mut random = Random::new(1);
+
+    let mut picked = random.sample(0..100, 10);
+    assert_eq!(picked.len(), 10);
+    picked.sort_unstable();
+    picked.dedup();
+    assert_eq!(picked.len(), 10);
+    assert!(picked.iter().all(|&i| i < 100));
+
+    let mut picked = random.sample(0..3, 10);
+    picked.sort_unstable();
+    assert_eq!(picked, [0, 1, 2]);
+
+    assert!(random.sample(0..100, 0).is_empty());
+    assert_eq!(random.sample(0..3, usize::MAX).len(), 3);
+    assert_eq!(Random::new(5).sample(0..1000, 3), Random::new(5).sample(0..1000, 3));
+  }
+}
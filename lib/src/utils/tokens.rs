@@ -22,3 +22,32 @@ macro_rules! iter_next {
     $iter.next().ok_or(Err::Eof)?
   }};
 }
+
+/// Compare two names for equality, ignoring ASCII case and any non-alphanumeric characters.
+///
+/// This is useful for matching user input against enumeration names, where e.g. `sci-fi`,
+/// `SciFi` and `Sci-Fi` should all be considered the same.
+///
+/// # Arguments
+///
+/// * `a` - The first name.
+/// * `b` - The second name.
+pub fn names_match(a: &str, b: &str) -> bool {
+  let a = a.chars().filter(char::is_ascii_alphanumeric);
+  let b = b.chars().filter(char::is_ascii_alphanumeric);
+  a.map(|c| c.to_ascii_lowercase()).eq(b.map(|c| c.to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::tokens::names_match;
+
+  #[test]
+  fn test_names_match() {
+    assert!(names_match("sci-fi", "Sci-Fi"));
+    assert!(names_match("SciFi", "Sci-Fi"));
+    assert!(names_match("tv series", "tvSeries"));
+    assert!(!names_match("scifi", "Sci-Fi-Horror"));
+    assert!(!names_match("movie", "tvMovie"));
+  }
+}
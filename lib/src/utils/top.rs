@@ -0,0 +1,80 @@
+#![warn(clippy::all)]
+
+//! Bounded selection of the best items, used to rank titles without sorting all of them.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Wraps an item to order it with a comparison function, so that it can be kept in a heap.
+struct Ranked<'c, T, F> {
+  item: T,
+  compare: &'c F,
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> PartialEq for Ranked<'_, T, F> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> Eq for Ranked<'_, T, F> {}
+
+impl<T, F: Fn(&T, &T) -> Ordering> PartialOrd for Ranked<'_, T, F> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> Ord for Ranked<'_, T, F> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    (self.compare)(&self.item, &other.item)
+  }
+}
+
+/// Return the best `n` items, best first, without collecting all of them, along with the
+/// number of items they were chosen from.
+///
+/// # Arguments
+///
+/// * `items` - The items to choose from.
+/// * `n` - The maximum number of items to return.
+/// * `compare` - Compares two items, better items being greater.
+pub fn top_n_by<T>(
+  items: impl Iterator<Item = T>,
+  n: usize,
+  compare: impl Fn(&T, &T) -> Ordering,
+) -> (Vec<T>, usize) {
+  if n == 0 {
+    return (Vec::new(), items.count());
+  }
+
+  let mut heap = BinaryHeap::with_capacity(n + 1);
+  let mut total = 0;
+
+  for item in items {
+    heap.push(Reverse(Ranked { item, compare: &compare }));
+    total += 1;
+
+    if heap.len() > n {
+      heap.pop();
+    }
+  }
+
+  let top = heap.into_sorted_vec().into_iter().map(|Reverse(ranked)| ranked.item).collect();
+  (top, total)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::utils::top::top_n_by;
+
+  #[test]
+  fn test_top_n_by() {
+    let numbers = [5, 1, 9, 3, 7];
+
+    assert_eq!(top_n_by(numbers.into_iter(), 3, Ord::cmp), (vec![9, 7, 5], 5));
+    assert_eq!(top_n_by(numbers.into_iter(), 3, |a, b| b.cmp(a)), (vec![1, 3, 5], 5));
+    assert_eq!(top_n_by(numbers.into_iter(), 10, Ord::cmp), (vec![9, 7, 5, 3, 1], 5));
+    assert_eq!(top_n_by(numbers.into_iter(), 0, Ord::cmp), (vec![], 5));
+  }
+}